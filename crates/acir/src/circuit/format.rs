@@ -0,0 +1,128 @@
+// The binary format of a Circuit is a header followed by the circuit as deflated MessagePack.
+//
+// The header is:
+// - the magic bytes `ACIR`
+// - the version of the format, as a little endian u16
+// - the CRC-32 of the payload which follows the header, as a little endian u32
+
+use flate2::bufread::{DeflateDecoder, DeflateEncoder};
+use flate2::{Compression, Crc};
+use std::io::prelude::*;
+
+use super::Circuit;
+
+const MAGIC: &[u8; 4] = b"ACIR";
+
+const HEADER_LEN: usize = MAGIC.len() + 2 + 4;
+
+/// The version of the binary format which is written by this version of ACIR.
+///
+/// Changing the `Gate`, `Directive` or `OPCODE` enums, other than by adding new variants
+/// after the existing ones, changes how circuits are represented, so this must be bumped
+/// whenever that happens. Circuits written with a different version are rejected rather than
+/// being misread.
+pub const FORMAT_VERSION: u16 = 1;
+
+/// The oldest version of the binary format which can still be read
+pub const MIN_SUPPORTED_FORMAT_VERSION: u16 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CircuitFormatError {
+    /// The bytes do not start with the ACIR header, they are either not a circuit
+    /// or were written before the binary format was versioned
+    MissingHeader,
+    UnsupportedVersion {
+        found: u16,
+    },
+    ChecksumMismatch {
+        expected: u32,
+        found: u32,
+    },
+    Decompression(String),
+    Deserialization(String),
+}
+
+impl std::fmt::Display for CircuitFormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CircuitFormatError::MissingHeader => write!(
+                f,
+                "the bytes are not an ACIR circuit, or were written by a compiler which predates the versioned format and must be compiled again"
+            ),
+            CircuitFormatError::UnsupportedVersion { found } if *found > FORMAT_VERSION => write!(
+                f,
+                "the circuit uses version {} of the ACIR format, which is newer than the latest supported version {}",
+                found, FORMAT_VERSION
+            ),
+            CircuitFormatError::UnsupportedVersion { found } => write!(
+                f,
+                "the circuit uses version {} of the ACIR format, which is older than the oldest supported version {}",
+                found, MIN_SUPPORTED_FORMAT_VERSION
+            ),
+            CircuitFormatError::ChecksumMismatch { expected, found } => write!(
+                f,
+                "the circuit is corrupted, its checksum is {:08x} but {:08x} was expected",
+                found, expected
+            ),
+            CircuitFormatError::Decompression(reason) => {
+                write!(f, "could not decompress the circuit: {}", reason)
+            }
+            CircuitFormatError::Deserialization(reason) => {
+                write!(
+                    f,
+                    "could not deserialize the circuit, it may contain gates which this version of ACIR does not know of: {}",
+                    reason
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for CircuitFormatError {}
+
+fn checksum(payload: &[u8]) -> u32 {
+    let mut crc = Crc::new();
+    crc.update(payload);
+    crc.sum()
+}
+
+pub(super) fn write_circuit(circuit: &Circuit) -> Vec<u8> {
+    let buf = rmp_serde::to_vec(circuit).expect("ice: circuits can always be serialized");
+    let mut deflater = DeflateEncoder::new(buf.as_slice(), Compression::best());
+    let mut payload = Vec::new();
+    deflater.read_to_end(&mut payload).expect("ice: reading from a slice cannot fail");
+
+    let mut bytes = Vec::with_capacity(HEADER_LEN + payload.len());
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&checksum(&payload).to_le_bytes());
+    bytes.extend_from_slice(&payload);
+    bytes
+}
+
+pub(super) fn read_circuit(bytes: &[u8]) -> Result<Circuit, CircuitFormatError> {
+    if bytes.len() < HEADER_LEN || &bytes[..MAGIC.len()] != MAGIC {
+        return Err(CircuitFormatError::MissingHeader);
+    }
+    let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+    let expected = u32::from_le_bytes([bytes[6], bytes[7], bytes[8], bytes[9]]);
+    let payload = &bytes[HEADER_LEN..];
+
+    // The version is checked first, as the checksum could be computed differently in other versions
+    if !(MIN_SUPPORTED_FORMAT_VERSION..=FORMAT_VERSION).contains(&version) {
+        return Err(CircuitFormatError::UnsupportedVersion { found: version });
+    }
+    let found = checksum(payload);
+    if found != expected {
+        return Err(CircuitFormatError::ChecksumMismatch { expected, found });
+    }
+
+    let mut deflater = DeflateDecoder::new(payload);
+    let mut buf = Vec::new();
+    deflater
+        .read_to_end(&mut buf)
+        .map_err(|err| CircuitFormatError::Decompression(err.to_string()))?;
+
+    // Older versions which are still supported would be migrated to the current representation here
+    rmp_serde::from_slice(&buf).map_err(|err| CircuitFormatError::Deserialization(err.to_string()))
+}
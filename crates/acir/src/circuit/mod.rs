@@ -1,16 +1,17 @@
+mod format;
 pub mod gate;
+mod text;
+mod validate;
 
+pub use format::{CircuitFormatError, FORMAT_VERSION, MIN_SUPPORTED_FORMAT_VERSION};
 pub use gate::Gate;
 use noir_field::FieldElement;
+pub use text::TextParseError;
+pub use validate::CircuitViolation;
 
 use crate::native_types::Witness;
-use rmp_serde;
 use serde::{Deserialize, Serialize};
 
-use flate2::bufread::{DeflateDecoder, DeflateEncoder};
-use flate2::Compression;
-use std::io::prelude::*;
-
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Circuit {
     pub current_witness_index: u32,
@@ -23,19 +24,33 @@ impl Circuit {
         self.current_witness_index + 1
     }
 
-    pub fn from_bytes(bytes: &[u8]) -> Circuit {
-        let mut deflater = DeflateDecoder::new(bytes);
-        let mut buf_d = Vec::new();
-        deflater.read_to_end(&mut buf_d).unwrap();
-        rmp_serde::from_slice(buf_d.as_slice()).unwrap()
+    /// Reads a circuit which was written by `to_bytes`, checking that it is intact
+    /// and that its format version is supported
+    pub fn from_bytes(bytes: &[u8]) -> Result<Circuit, CircuitFormatError> {
+        format::read_circuit(bytes)
     }
 
+    /// Writes the circuit in the versioned binary format
     pub fn to_bytes(&self) -> Vec<u8> {
-        let buf = rmp_serde::to_vec(&self).unwrap();
-        let mut deflater = DeflateEncoder::new(buf.as_slice(), Compression::best());
-        let mut buf_c = Vec::new();
-        deflater.read_to_end(&mut buf_c).unwrap();
-        buf_c
+        format::write_circuit(self)
+    }
+
+    /// Checks that the circuit is well formed, returning every way in which it is not.
+    ///
+    /// This only checks the structure of the circuit, such as whether its witnesses are in range
+    /// and whether its gadget calls have the right number of inputs and outputs, not whether it is satisfiable.
+    pub fn validate(&self) -> Vec<CircuitViolation> {
+        validate::validate_circuit(self)
+    }
+
+    /// Writes the circuit in the textual form which is described in the `text` module
+    pub fn to_text(&self) -> String {
+        text::print_circuit(self)
+    }
+
+    /// Reads a circuit which was written by `to_text`, or by hand
+    pub fn from_text(text: &str) -> Result<Circuit, TextParseError> {
+        text::parse_circuit(text)
     }
 }
 
@@ -63,7 +78,7 @@ impl Default for Selector {
 
 #[cfg(test)]
 mod test {
-    use super::{gate::AndGate, Circuit, Gate, PublicInputs};
+    use super::{gate::AndGate, Circuit, CircuitFormatError, Gate, PublicInputs, FORMAT_VERSION};
     use crate::native_types::Witness;
     use noir_field::FieldElement;
 
@@ -119,7 +134,48 @@ mod test {
         let bytes = circuit.to_bytes();
         println!("bytes: {:?}", bytes);
 
-        let deserialized = Circuit::from_bytes(bytes.as_slice());
+        let deserialized = Circuit::from_bytes(bytes.as_slice()).unwrap();
         assert_eq!(circuit, deserialized);
     }
+
+    fn simple_circuit() -> Circuit {
+        Circuit {
+            current_witness_index: 2,
+            gates: vec![Gate::Range(Witness(1), 8)],
+            public_inputs: PublicInputs(vec![Witness(2)]),
+        }
+    }
+
+    #[test]
+    fn test_bytes_have_a_header() {
+        let bytes = simple_circuit().to_bytes();
+        assert_eq!(&bytes[..4], b"ACIR");
+        assert_eq!(u16::from_le_bytes([bytes[4], bytes[5]]), FORMAT_VERSION);
+    }
+
+    #[test]
+    fn test_invalid_bytes_are_rejected() {
+        let bytes = simple_circuit().to_bytes();
+
+        // Not a circuit, or written before the format was versioned
+        assert_eq!(Circuit::from_bytes(&bytes[10..]), Err(CircuitFormatError::MissingHeader));
+        assert_eq!(Circuit::from_bytes(&[]), Err(CircuitFormatError::MissingHeader));
+
+        // Written by a newer compiler
+        let mut newer = bytes.clone();
+        newer[4..6].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+        assert_eq!(
+            Circuit::from_bytes(&newer),
+            Err(CircuitFormatError::UnsupportedVersion { found: FORMAT_VERSION + 1 })
+        );
+
+        // Corrupted
+        let mut corrupted = bytes.clone();
+        let last = corrupted.len() - 1;
+        corrupted[last] ^= 0xff;
+        assert!(matches!(
+            Circuit::from_bytes(&corrupted),
+            Err(CircuitFormatError::ChecksumMismatch { .. })
+        ));
+    }
 }
@@ -0,0 +1,672 @@
+//! A textual form of a [`Circuit`], which can be read and diffed by people and written by hand
+//! for tests. Printing a circuit and parsing the text gives back exactly the same circuit.
+//!
+//! A circuit is written one item per line. Blank lines, and lines starting with `//`, are ignored.
+//! The first lines are the header, which gives the current witness index and the public inputs:
+//!
+//! ```text
+//! current_witness_index 4
+//! public_inputs [x1 x2]
+//! ```
+//!
+//! Then each gate follows on its own line, in order. Witnesses are written as `x` followed by
+//! their index, and field elements as decimal integers, which may be negative, or as hexadecimal
+//! integers starting with `0x`.
+//!
+//! An expression is a sum of terms, where each term is either a coefficient multiplied by
+//! two witnesses, a coefficient multiplied by one witness, or a constant. For example,
+//! `2*x1*x2 + -1*x3 + 5`. The multiplication terms and then the linear terms are written
+//! in the order they are stored in, followed by the constant.
+//!
+//! The gates are written as:
+//!
+//! ```text
+//! arithmetic 2*x1*x2 + -1*x3 + 5 = 0
+//! range x1 bits=8
+//! and x1 x2 bits=8 -> x3
+//! xor x1 x2 bits=8 -> x3
+//! gadget sha256 [x1:8 x2:8] -> [x3 x4]
+//! ```
+//!
+//! where the inputs of a gadget call are written as `witness:num_bits`. The directives are written as:
+//!
+//! ```text
+//! invert x1 -> x2
+//! quotient (1*x1 + 0) / (1*x2 + 0) -> x3 x4
+//! quotient (1*x1 + 0) / (1*x2 + 0) if (1*x5 + 0) -> x3 x4
+//! truncate x1 bits=8 -> x2 x3
+//! odd_range x1 bits=8 -> x2 x3
+//! split x1 bits=8 -> [x2 x3]
+//! ```
+//!
+//! where `quotient` writes the quotient and then the remainder, `truncate` writes the truncated
+//! value and then the part which was truncated away, and `odd_range` writes the highest bit
+//! and then the remaining bits.
+
+use std::fmt::Write;
+
+use noir_field::FieldElement;
+
+use super::gate::{AndGate, Directive, GadgetCall, GadgetInput, XorGate};
+use super::{Circuit, Gate, PublicInputs};
+use crate::native_types::{Expression, Witness};
+use crate::OPCODE;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for TextParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for TextParseError {}
+
+pub(super) fn print_circuit(circuit: &Circuit) -> String {
+    let mut text = String::new();
+    writeln!(text, "current_witness_index {}", circuit.current_witness_index).unwrap();
+    writeln!(text, "public_inputs [{}]", witness_list(&circuit.public_inputs.0)).unwrap();
+    for gate in &circuit.gates {
+        writeln!(text, "{}", print_gate(gate)).unwrap();
+    }
+    text
+}
+
+fn print_gate(gate: &Gate) -> String {
+    match gate {
+        Gate::Arithmetic(expr) => format!("arithmetic {} = 0", print_expression(expr)),
+        Gate::Range(w, num_bits) => format!("range {} bits={}", witness(w), num_bits),
+        Gate::And(AndGate { a, b, result, num_bits }) => {
+            format!("and {} {} bits={} -> {}", witness(a), witness(b), num_bits, witness(result))
+        }
+        Gate::Xor(XorGate { a, b, result, num_bits }) => {
+            format!("xor {} {} bits={} -> {}", witness(a), witness(b), num_bits, witness(result))
+        }
+        Gate::GadgetCall(GadgetCall { name, inputs, outputs }) => {
+            let inputs: Vec<_> = inputs
+                .iter()
+                .map(|input| format!("{}:{}", witness(&input.witness), input.num_bits))
+                .collect();
+            format!("gadget {} [{}] -> [{}]", name, inputs.join(" "), witness_list(outputs))
+        }
+        Gate::Directive(Directive::Invert { x, result }) => {
+            format!("invert {} -> {}", witness(x), witness(result))
+        }
+        Gate::Directive(Directive::Quotient { a, b, q, r, predicate }) => {
+            let predicate = match predicate {
+                Some(predicate) => format!(" if ({})", print_expression(predicate)),
+                None => String::new(),
+            };
+            format!(
+                "quotient ({}) / ({}){} -> {} {}",
+                print_expression(a),
+                print_expression(b),
+                predicate,
+                witness(q),
+                witness(r)
+            )
+        }
+        Gate::Directive(Directive::Truncate { a, b, c, bit_size }) => {
+            format!("truncate {} bits={} -> {} {}", witness(a), bit_size, witness(b), witness(c))
+        }
+        Gate::Directive(Directive::Oddrange { a, b, r, bit_size }) => {
+            format!("odd_range {} bits={} -> {} {}", witness(a), bit_size, witness(b), witness(r))
+        }
+        Gate::Directive(Directive::Split { a, b, bit_size }) => {
+            format!("split {} bits={} -> [{}]", witness(a), bit_size, witness_list(b))
+        }
+    }
+}
+
+fn print_expression(expr: &Expression) -> String {
+    let mut terms = Vec::with_capacity(expr.mul_terms.len() + expr.linear_combinations.len() + 1);
+    for (coefficient, lhs, rhs) in &expr.mul_terms {
+        terms.push(format!("{}*{}*{}", coefficient, witness(lhs), witness(rhs)));
+    }
+    for (coefficient, w) in &expr.linear_combinations {
+        terms.push(format!("{}*{}", coefficient, witness(w)));
+    }
+    // The constant is always written, so that the terms are read back in the same order
+    terms.push(expr.q_c.to_string());
+    terms.join(" + ")
+}
+
+fn witness(witness: &Witness) -> String {
+    format!("x{}", witness.witness_index())
+}
+
+fn witness_list(witnesses: &[Witness]) -> String {
+    witnesses.iter().map(witness).collect::<Vec<_>>().join(" ")
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Word(String),
+    Punct(char),
+    Arrow,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Word(word) => write!(f, "`{}`", word),
+            Token::Punct(punct) => write!(f, "`{}`", punct),
+            Token::Arrow => write!(f, "`->`"),
+        }
+    }
+}
+
+// A `-` may only start a word, for negative field elements
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '^'
+}
+
+/// The tokens of a single line, along with the line they are on
+struct Line {
+    line: usize,
+    // The tokens and the columns they start at
+    tokens: Vec<(usize, Token)>,
+    // The column just after the end of the line, for errors about missing tokens
+    end: usize,
+    position: usize,
+}
+
+impl Line {
+    fn tokenize(line: usize, text: &str) -> Result<Line, TextParseError> {
+        let mut tokens = Vec::new();
+        let mut chars = text.char_indices().peekable();
+        while let Some((start, c)) = chars.next() {
+            let column = start + 1;
+            match c {
+                c if c.is_whitespace() => (),
+                '-' if matches!(chars.peek(), Some((_, '>'))) => {
+                    chars.next();
+                    tokens.push((column, Token::Arrow));
+                }
+                '[' | ']' | '(' | ')' | '*' | '+' | '/' | '=' | ':' => {
+                    tokens.push((column, Token::Punct(c)))
+                }
+                c if is_word_char(c) || c == '-' => {
+                    let mut word = c.to_string();
+                    while let Some((_, c)) = chars.peek().filter(|(_, c)| is_word_char(*c)) {
+                        word.push(*c);
+                        chars.next();
+                    }
+                    tokens.push((column, Token::Word(word)));
+                }
+                c => {
+                    return Err(TextParseError {
+                        line,
+                        column,
+                        message: format!("unexpected character `{}`", c),
+                    })
+                }
+            }
+        }
+        Ok(Line { line, tokens, end: text.len() + 1, position: 0 })
+    }
+
+    fn error<T>(&self, column: usize, message: String) -> Result<T, TextParseError> {
+        Err(TextParseError { line: self.line, column, message })
+    }
+
+    fn column(&self) -> usize {
+        self.tokens.get(self.position).map_or(self.end, |(column, _)| *column)
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(_, token)| token)
+    }
+
+    fn next(&mut self, expected: &str) -> Result<(usize, Token), TextParseError> {
+        match self.tokens.get(self.position) {
+            Some(token) => {
+                self.position += 1;
+                Ok(token.clone())
+            }
+            None => self.error(self.end, format!("expected {} but the line ended", expected)),
+        }
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), TextParseError> {
+        let (column, token) = self.next(&expected.to_string())?;
+        if token != expected {
+            return self.error(column, format!("expected {} but found {}", expected, token));
+        }
+        Ok(())
+    }
+
+    fn eat(&mut self, expected: Token) -> bool {
+        if self.peek() == Some(&expected) {
+            self.position += 1;
+            return true;
+        }
+        false
+    }
+
+    fn word(&mut self, expected: &str) -> Result<(usize, String), TextParseError> {
+        match self.next(expected)? {
+            (column, Token::Word(word)) => Ok((column, word)),
+            (column, token) => {
+                self.error(column, format!("expected {} but found {}", expected, token))
+            }
+        }
+    }
+
+    fn keyword(&mut self, keyword: &str) -> Result<(), TextParseError> {
+        self.expect(Token::Word(keyword.to_owned()))
+    }
+
+    fn finish(&self) -> Result<(), TextParseError> {
+        match self.tokens.get(self.position) {
+            Some((column, token)) => {
+                self.error(*column, format!("expected the end of the line but found {}", token))
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn integer(&mut self, expected: &str) -> Result<u32, TextParseError> {
+        let (column, word) = self.word(expected)?;
+        match word.parse() {
+            Ok(integer) => Ok(integer),
+            Err(_) => self.error(column, format!("expected {} but found `{}`", expected, word)),
+        }
+    }
+
+    fn num_bits(&mut self) -> Result<u32, TextParseError> {
+        self.keyword("bits")?;
+        self.expect(Token::Punct('='))?;
+        self.integer("a number of bits")
+    }
+
+    fn witness(&mut self) -> Result<Witness, TextParseError> {
+        let (column, word) = self.word("a witness")?;
+        match parse_witness(&word) {
+            Some(witness) => Ok(witness),
+            None => self.error(column, format!("expected a witness but found `{}`", word)),
+        }
+    }
+
+    fn witness_list(&mut self) -> Result<Vec<Witness>, TextParseError> {
+        self.expect(Token::Punct('['))?;
+        let mut witnesses = Vec::new();
+        while !self.eat(Token::Punct(']')) {
+            witnesses.push(self.witness()?);
+        }
+        Ok(witnesses)
+    }
+
+    fn field_element(&mut self) -> Result<FieldElement, TextParseError> {
+        let (column, word) = self.word("a field element")?;
+        match parse_field_element(&word) {
+            Some(value) => Ok(value),
+            None => self.error(column, format!("expected a field element but found `{}`", word)),
+        }
+    }
+
+    /// Parses an expression, up to but not including the token which follows it
+    fn expression(&mut self) -> Result<Expression, TextParseError> {
+        let mut expr = Expression::default();
+        let mut has_constant = false;
+        loop {
+            let column = self.column();
+            let coefficient = self.field_element()?;
+            if self.eat(Token::Punct('*')) {
+                let lhs = self.witness()?;
+                if self.eat(Token::Punct('*')) {
+                    let rhs = self.witness()?;
+                    expr.mul_terms.push((coefficient, lhs, rhs));
+                } else {
+                    expr.linear_combinations.push((coefficient, lhs));
+                }
+            } else {
+                if has_constant {
+                    return self
+                        .error(column, "an expression can only have one constant".to_owned());
+                }
+                has_constant = true;
+                expr.q_c = coefficient;
+            }
+            if !self.eat(Token::Punct('+')) {
+                return Ok(expr);
+            }
+        }
+    }
+
+    fn parenthesized_expression(&mut self) -> Result<Expression, TextParseError> {
+        self.expect(Token::Punct('('))?;
+        let expr = self.expression()?;
+        self.expect(Token::Punct(')'))?;
+        Ok(expr)
+    }
+
+    fn gadget_input(&mut self) -> Result<GadgetInput, TextParseError> {
+        let witness = self.witness()?;
+        self.expect(Token::Punct(':'))?;
+        let num_bits = self.integer("a number of bits")?;
+        Ok(GadgetInput { witness, num_bits })
+    }
+
+    fn gate(&mut self) -> Result<Gate, TextParseError> {
+        let (column, name) = self.word("a gate")?;
+        let gate = match name.as_str() {
+            "arithmetic" => {
+                let expr = self.expression()?;
+                self.expect(Token::Punct('='))?;
+                self.keyword("0")?;
+                Gate::Arithmetic(expr)
+            }
+            "range" => {
+                let witness = self.witness()?;
+                Gate::Range(witness, self.num_bits()?)
+            }
+            "and" | "xor" => {
+                let a = self.witness()?;
+                let b = self.witness()?;
+                let num_bits = self.num_bits()?;
+                self.expect(Token::Arrow)?;
+                let result = self.witness()?;
+                if name == "and" {
+                    Gate::And(AndGate { a, b, result, num_bits })
+                } else {
+                    Gate::Xor(XorGate { a, b, result, num_bits })
+                }
+            }
+            "gadget" => {
+                let (column, opcode) = self.word("the name of a gadget")?;
+                let name = match parse_opcode(&opcode) {
+                    Some(name) => name,
+                    None => return self.error(column, format!("unknown gadget `{}`", opcode)),
+                };
+                self.expect(Token::Punct('['))?;
+                let mut inputs = Vec::new();
+                while !self.eat(Token::Punct(']')) {
+                    inputs.push(self.gadget_input()?);
+                }
+                self.expect(Token::Arrow)?;
+                let outputs = self.witness_list()?;
+                Gate::GadgetCall(GadgetCall { name, inputs, outputs })
+            }
+            "invert" => {
+                let x = self.witness()?;
+                self.expect(Token::Arrow)?;
+                let result = self.witness()?;
+                Gate::Directive(Directive::Invert { x, result })
+            }
+            "quotient" => {
+                let a = self.parenthesized_expression()?;
+                self.expect(Token::Punct('/'))?;
+                let b = self.parenthesized_expression()?;
+                let predicate = if self.eat(Token::Word("if".to_owned())) {
+                    Some(Box::new(self.parenthesized_expression()?))
+                } else {
+                    None
+                };
+                self.expect(Token::Arrow)?;
+                let q = self.witness()?;
+                let r = self.witness()?;
+                Gate::Directive(Directive::Quotient { a, b, q, r, predicate })
+            }
+            "truncate" => {
+                let a = self.witness()?;
+                let bit_size = self.num_bits()?;
+                self.expect(Token::Arrow)?;
+                let b = self.witness()?;
+                let c = self.witness()?;
+                Gate::Directive(Directive::Truncate { a, b, c, bit_size })
+            }
+            "odd_range" => {
+                let a = self.witness()?;
+                let bit_size = self.num_bits()?;
+                self.expect(Token::Arrow)?;
+                let b = self.witness()?;
+                let r = self.witness()?;
+                Gate::Directive(Directive::Oddrange { a, b, r, bit_size })
+            }
+            "split" => {
+                let a = self.witness()?;
+                let bit_size = self.num_bits()?;
+                self.expect(Token::Arrow)?;
+                let b = self.witness_list()?;
+                Gate::Directive(Directive::Split { a, b, bit_size })
+            }
+            _ => return self.error(column, format!("unknown gate `{}`", name)),
+        };
+        self.finish()?;
+        Ok(gate)
+    }
+}
+
+fn parse_witness(word: &str) -> Option<Witness> {
+    let index = word.strip_prefix('x')?;
+    if !index.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    index.parse().ok().map(Witness)
+}
+
+fn parse_field_element(word: &str) -> Option<FieldElement> {
+    let (negative, word) = match word.strip_prefix('-') {
+        Some(word) => (true, word),
+        None => (false, word),
+    };
+    let value = if let Some(exponent) = word.strip_prefix("2^") {
+        let exponent: u32 = exponent.parse().ok()?;
+        FieldElement::from(2_i128).pow(&FieldElement::from(exponent as i128))
+    } else if word.starts_with(|c: char| c.is_ascii_digit()) {
+        FieldElement::try_from_str(word)?
+    } else {
+        return None;
+    };
+    Some(if negative { -value } else { value })
+}
+
+fn parse_opcode(name: &str) -> Option<OPCODE> {
+    // AES is not looked up by name, as there is no gadget for it in the standard library
+    if name == OPCODE::AES.name() {
+        return Some(OPCODE::AES);
+    }
+    OPCODE::lookup(name)
+}
+
+pub(super) fn parse_circuit(text: &str) -> Result<Circuit, TextParseError> {
+    let mut current_witness_index = None;
+    let mut public_inputs = None;
+    let mut gates = Vec::new();
+
+    for (index, text) in text.lines().enumerate() {
+        if text.trim_start().starts_with("//") {
+            continue;
+        }
+        let mut line = Line::tokenize(index + 1, text)?;
+        match line.peek().cloned() {
+            None => continue,
+            Some(Token::Word(word)) if word == "current_witness_index" => {
+                if current_witness_index.is_some() || !gates.is_empty() {
+                    return line.error(
+                        1,
+                        "the current witness index must be given once, before the gates".to_owned(),
+                    );
+                }
+                line.position += 1;
+                current_witness_index = Some(line.integer("a witness index")?);
+                line.finish()?;
+            }
+            Some(Token::Word(word)) if word == "public_inputs" => {
+                if public_inputs.is_some() || !gates.is_empty() {
+                    return line.error(
+                        1,
+                        "the public inputs must be given once, before the gates".to_owned(),
+                    );
+                }
+                line.position += 1;
+                public_inputs = Some(PublicInputs(line.witness_list()?));
+                line.finish()?;
+            }
+            Some(_) => {
+                if current_witness_index.is_none() || public_inputs.is_none() {
+                    return line.error(
+                        line.column(),
+                        "the circuit must start with its current witness index and public inputs"
+                            .to_owned(),
+                    );
+                }
+                gates.push(line.gate()?);
+            }
+        }
+    }
+
+    match (current_witness_index, public_inputs) {
+        (Some(current_witness_index), Some(public_inputs)) => {
+            Ok(Circuit { current_witness_index, gates, public_inputs })
+        }
+        _ => Err(TextParseError {
+            line: text.lines().count().max(1),
+            column: 1,
+            message: "the circuit must start with its current witness index and public inputs"
+                .to_owned(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::gate::{AndGate, Directive, GadgetCall, GadgetInput, XorGate};
+    use super::super::{Circuit, Gate, PublicInputs};
+    use crate::native_types::{Expression, Witness};
+    use crate::OPCODE;
+    use noir_field::FieldElement;
+
+    fn expression() -> Expression {
+        Expression {
+            mul_terms: vec![(FieldElement::from(2_i128), Witness(1), Witness(2))],
+            linear_combinations: vec![
+                (-FieldElement::one(), Witness(3)),
+                (FieldElement::zero(), Witness(1)),
+            ],
+            q_c: FieldElement::from_hex("0x1234567890abcdef1234567890abcdef1234567890abcdef")
+                .unwrap(),
+        }
+    }
+
+    fn every_gate() -> Circuit {
+        Circuit {
+            current_witness_index: 12,
+            gates: vec![
+                Gate::Arithmetic(expression()),
+                Gate::Arithmetic(Expression::default()),
+                Gate::Range(Witness(1), 8),
+                Gate::And(AndGate {
+                    a: Witness(1),
+                    b: Witness(2),
+                    result: Witness(3),
+                    num_bits: 4,
+                }),
+                Gate::Xor(XorGate {
+                    a: Witness(1),
+                    b: Witness(2),
+                    result: Witness(3),
+                    num_bits: 4,
+                }),
+                Gate::GadgetCall(GadgetCall {
+                    name: OPCODE::SHA256,
+                    inputs: vec![
+                        GadgetInput { witness: Witness(1), num_bits: 8 },
+                        GadgetInput { witness: Witness(2), num_bits: 8 },
+                    ],
+                    outputs: vec![Witness(4), Witness(5)],
+                }),
+                Gate::GadgetCall(GadgetCall { name: OPCODE::AES, inputs: vec![], outputs: vec![] }),
+                Gate::Directive(Directive::Invert { x: Witness(1), result: Witness(6) }),
+                Gate::Directive(Directive::Quotient {
+                    a: expression(),
+                    b: Expression::from(&Witness(2)),
+                    q: Witness(7),
+                    r: Witness(8),
+                    predicate: None,
+                }),
+                Gate::Directive(Directive::Quotient {
+                    a: Expression::from(&Witness(1)),
+                    b: Expression::from(&Witness(2)),
+                    q: Witness(7),
+                    r: Witness(8),
+                    predicate: Some(Box::new(Expression::from(&Witness(3)))),
+                }),
+                Gate::Directive(Directive::Truncate {
+                    a: Witness(1),
+                    b: Witness(9),
+                    c: Witness(10),
+                    bit_size: 16,
+                }),
+                Gate::Directive(Directive::Oddrange {
+                    a: Witness(1),
+                    b: Witness(11),
+                    r: Witness(12),
+                    bit_size: 16,
+                }),
+                Gate::Directive(Directive::Split {
+                    a: Witness(1),
+                    b: vec![Witness(9), Witness(10)],
+                    bit_size: 2,
+                }),
+            ],
+            public_inputs: PublicInputs(vec![Witness(1), Witness(2)]),
+        }
+    }
+
+    #[test]
+    fn round_trips_every_gate() {
+        let circuit = every_gate();
+        let text = circuit.to_text();
+        assert_eq!(Circuit::from_text(&text).unwrap(), circuit);
+        assert_eq!(Circuit::from_text(&text).unwrap().to_text(), text);
+    }
+
+    #[test]
+    fn parses_hand_written_circuits() {
+        let text = "
+            // x3 = x1 * x2
+            current_witness_index 3
+            public_inputs []
+
+            arithmetic 1*x1*x2 + -1*x3 = 0
+            range x3 bits=0x8
+        ";
+        let error = Circuit::from_text(text).unwrap_err();
+        assert_eq!((error.line, error.column), (7, 27));
+
+        let circuit = Circuit::from_text(&text.replace("0x8", "8")).unwrap();
+        assert_eq!(circuit.current_witness_index, 3);
+        assert_eq!(circuit.gates.len(), 2);
+        assert_eq!(
+            circuit.gates[0],
+            Gate::Arithmetic(Expression {
+                mul_terms: vec![(FieldElement::one(), Witness(1), Witness(2))],
+                linear_combinations: vec![(-FieldElement::one(), Witness(3))],
+                q_c: FieldElement::zero(),
+            })
+        );
+    }
+
+    #[test]
+    fn reports_where_the_text_is_invalid() {
+        let header = "current_witness_index 3\npublic_inputs [x1]\n";
+        let error = |gate: &str| {
+            let error = Circuit::from_text(&format!("{}{}", header, gate)).unwrap_err();
+            (error.line, error.column)
+        };
+        assert_eq!(error("multiply x1 x2"), (3, 1));
+        assert_eq!(error("range y1 bits=8"), (3, 7));
+        assert_eq!(error("and x1 x2 bits=8"), (3, 17));
+        assert_eq!(error("gadget sha512 [] -> []"), (3, 8));
+        assert_eq!(error("invert x1 -> x2 x3"), (3, 17));
+        assert_eq!(error("arithmetic 1 + 2 = 0"), (3, 16));
+        assert!(Circuit::from_text("range x1 bits=8").is_err());
+    }
+}
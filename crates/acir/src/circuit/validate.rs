@@ -0,0 +1,237 @@
+use std::collections::BTreeSet;
+
+use super::gate::{AndGate, Directive, GadgetCall, XorGate};
+use super::{Circuit, Gate};
+use crate::native_types::{Expression, Witness};
+use crate::opcode::InputSize;
+use crate::OPCODE;
+
+/// A way in which a circuit is malformed, the gates are referred to by their position in the circuit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CircuitViolation {
+    /// A gate uses a witness whose index is greater than the current witness index of the circuit
+    WitnessOutOfRange {
+        gate: usize,
+        witness: Witness,
+    },
+    PublicInputOutOfRange {
+        witness: Witness,
+    },
+    /// A public input which is not used by any gate, so it does not constrain the proof
+    UnusedPublicInput {
+        witness: Witness,
+    },
+    GadgetInputCount {
+        gate: usize,
+        opcode: OPCODE,
+        expected: u128,
+        found: usize,
+    },
+    GadgetOutputCount {
+        gate: usize,
+        opcode: OPCODE,
+        expected: u128,
+        found: usize,
+    },
+    /// A split directive which does not decompose its input into `bit_size` bits
+    SplitBitCount {
+        gate: usize,
+        bit_size: u32,
+        found: usize,
+    },
+}
+
+impl std::fmt::Display for CircuitViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CircuitViolation::WitnessOutOfRange { gate, witness } => write!(
+                f,
+                "gate {} uses the witness x{}, which is greater than the current witness index",
+                gate,
+                witness.witness_index()
+            ),
+            CircuitViolation::PublicInputOutOfRange { witness } => write!(
+                f,
+                "the public input x{} is greater than the current witness index",
+                witness.witness_index()
+            ),
+            CircuitViolation::UnusedPublicInput { witness } => {
+                write!(f, "the public input x{} is not used by any gate", witness.witness_index())
+            }
+            CircuitViolation::GadgetInputCount { gate, opcode, expected, found } => write!(
+                f,
+                "gate {} calls {} with {} inputs, but it takes {}",
+                gate, opcode, found, expected
+            ),
+            CircuitViolation::GadgetOutputCount { gate, opcode, expected, found } => write!(
+                f,
+                "gate {} calls {} with {} outputs, but it returns {}",
+                gate, opcode, found, expected
+            ),
+            CircuitViolation::SplitBitCount { gate, bit_size, found } => write!(
+                f,
+                "gate {} splits a witness into {} bits, but has {} witnesses for them",
+                gate, bit_size, found
+            ),
+        }
+    }
+}
+
+pub(super) fn validate_circuit(circuit: &Circuit) -> Vec<CircuitViolation> {
+    let mut violations = Vec::new();
+    let mut used_witnesses = BTreeSet::new();
+
+    for (index, gate) in circuit.gates.iter().enumerate() {
+        for witness in gate_witnesses(gate) {
+            if witness.witness_index() > circuit.current_witness_index {
+                violations.push(CircuitViolation::WitnessOutOfRange { gate: index, witness });
+            }
+            used_witnesses.insert(witness);
+        }
+
+        match gate {
+            Gate::GadgetCall(GadgetCall { name, inputs, outputs }) => {
+                // AES does not have a definition yet
+                if *name == OPCODE::AES {
+                    continue;
+                }
+                let definition = name.definition();
+                if let InputSize::Fixed(expected) = definition.input_size {
+                    if inputs.len() as u128 != expected {
+                        violations.push(CircuitViolation::GadgetInputCount {
+                            gate: index,
+                            opcode: *name,
+                            expected,
+                            found: inputs.len(),
+                        });
+                    }
+                }
+                if outputs.len() as u128 != definition.output_size.0 {
+                    violations.push(CircuitViolation::GadgetOutputCount {
+                        gate: index,
+                        opcode: *name,
+                        expected: definition.output_size.0,
+                        found: outputs.len(),
+                    });
+                }
+            }
+            Gate::Directive(Directive::Split { b, bit_size, .. }) => {
+                if b.len() != *bit_size as usize {
+                    violations.push(CircuitViolation::SplitBitCount {
+                        gate: index,
+                        bit_size: *bit_size,
+                        found: b.len(),
+                    });
+                }
+            }
+            _ => (),
+        }
+    }
+
+    for witness in &circuit.public_inputs.0 {
+        if witness.witness_index() > circuit.current_witness_index {
+            violations.push(CircuitViolation::PublicInputOutOfRange { witness: *witness });
+        } else if !used_witnesses.contains(witness) {
+            violations.push(CircuitViolation::UnusedPublicInput { witness: *witness });
+        }
+    }
+
+    violations
+}
+
+/// Returns every witness which a gate reads or writes
+fn gate_witnesses(gate: &Gate) -> Vec<Witness> {
+    match gate {
+        Gate::Arithmetic(expr) => expression_witnesses(expr),
+        Gate::Range(witness, _) => vec![*witness],
+        Gate::And(AndGate { a, b, result, .. }) | Gate::Xor(XorGate { a, b, result, .. }) => {
+            vec![*a, *b, *result]
+        }
+        Gate::GadgetCall(GadgetCall { inputs, outputs, .. }) => {
+            inputs.iter().map(|input| input.witness).chain(outputs.iter().copied()).collect()
+        }
+        Gate::Directive(Directive::Invert { x, result }) => vec![*x, *result],
+        Gate::Directive(Directive::Quotient { a, b, q, r, predicate }) => {
+            let mut witnesses = expression_witnesses(a);
+            witnesses.extend(expression_witnesses(b));
+            if let Some(predicate) = predicate {
+                witnesses.extend(expression_witnesses(predicate));
+            }
+            witnesses.extend([*q, *r]);
+            witnesses
+        }
+        Gate::Directive(Directive::Truncate { a, b, c, .. }) => vec![*a, *b, *c],
+        Gate::Directive(Directive::Oddrange { a, b, r, .. }) => vec![*a, *b, *r],
+        Gate::Directive(Directive::Split { a, b, .. }) => {
+            std::iter::once(*a).chain(b.iter().copied()).collect()
+        }
+    }
+}
+
+fn expression_witnesses(expr: &Expression) -> Vec<Witness> {
+    expr.mul_terms
+        .iter()
+        .flat_map(|(_, lhs, rhs)| [*lhs, *rhs])
+        .chain(expr.linear_combinations.iter().map(|(_, witness)| *witness))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CircuitViolation;
+    use crate::circuit::Circuit;
+    use crate::native_types::Witness;
+    use crate::OPCODE;
+
+    #[test]
+    fn reports_every_violation() {
+        let circuit = Circuit::from_text(
+            "
+            current_witness_index 4
+            public_inputs [x1 x2 x7]
+            arithmetic 1*x1*x3 + -1*x5 + 0 = 0
+            gadget pedersen [x1:254] -> [x4]
+            gadget fixed_base_scalar_mul [x1:254 x3:254] -> [x3 x4]
+            split x1 bits=3 -> [x3 x4]
+            ",
+        )
+        .unwrap();
+
+        assert_eq!(
+            circuit.validate(),
+            vec![
+                CircuitViolation::WitnessOutOfRange { gate: 0, witness: Witness(5) },
+                CircuitViolation::GadgetOutputCount {
+                    gate: 1,
+                    opcode: OPCODE::Pedersen,
+                    expected: 2,
+                    found: 1
+                },
+                CircuitViolation::GadgetInputCount {
+                    gate: 2,
+                    opcode: OPCODE::FixedBaseScalarMul,
+                    expected: 1,
+                    found: 2
+                },
+                CircuitViolation::SplitBitCount { gate: 3, bit_size: 3, found: 2 },
+                CircuitViolation::UnusedPublicInput { witness: Witness(2) },
+                CircuitViolation::PublicInputOutOfRange { witness: Witness(7) },
+            ]
+        );
+    }
+
+    #[test]
+    fn well_formed_circuits_are_valid() {
+        let circuit = Circuit::from_text(
+            "
+            current_witness_index 5
+            public_inputs [x1]
+            arithmetic 1*x1*x2 + -1*x3 + 0 = 0
+            gadget hash_to_field [x1:8 x2:8] -> [x4]
+            split x1 bits=2 -> [x4 x5]
+            ",
+        )
+        .unwrap();
+        assert_eq!(circuit.validate(), vec![]);
+    }
+}
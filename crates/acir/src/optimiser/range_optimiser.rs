@@ -1,19 +1,207 @@
+// Removes the range constraints which are implied by other constraints in the circuit:
+// - When a witness is range constrained more than once, only the tightest range is kept,
+//   in place of the first range constraint on the witness.
+// - A range constraint is dropped when the witness is decomposed into at most as many bits by a
+//   split directive. The directive itself does not constrain anything, so this is only done when
+//   the circuit also constrains every bit to be boolean, and the witness to be the sum of its bits.
+//
 // XXX: We could alleviate a runtime check from noir
 // By casting directly
 // Example:
-// priv z1 =  x as u32 
-// priv z2 =  x as u16 
+// priv z1 =  x as u32
+// priv z2 =  x as u16
 //
 // The IR would see both casts and replace it with
-// 
-// 
+//
+//
 // priv z1 = x as u16;
 // priv z2 = x as u16;
 //
-// 
+//
 // Then maybe another optimisation could be done so that it transforms into
 //
 // priv z1 = x as u16
 // priv z2 = z1
 // This is what I would call a general optimisation, so it could live inside of the IR module
-// A more specific optimisation would be to have z2 = z1 not use a gate (copy_from_to), this is more specific to plonk-aztec and would not live in this module
\ No newline at end of file
+// A more specific optimisation would be to have z2 = z1 not use a gate (copy_from_to), this is more specific to plonk-aztec and would not live in this module
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Mul;
+
+use noir_field::FieldElement;
+
+use crate::circuit::{gate::Directive, Gate};
+use crate::native_types::{Expression, Witness};
+
+pub struct RangeOptimiser;
+
+impl RangeOptimiser {
+    pub fn optimise(gates: Vec<Gate>) -> Vec<Gate> {
+        let split_bits = proven_bit_sizes(&gates);
+
+        let mut tightest_ranges: BTreeMap<Witness, u32> = BTreeMap::new();
+        for gate in &gates {
+            if let Gate::Range(witness, num_bits) = gate {
+                let range = tightest_ranges.entry(*witness).or_insert(*num_bits);
+                *range = (*range).min(*num_bits);
+            }
+        }
+
+        let mut constrained = BTreeSet::new();
+        gates
+            .into_iter()
+            .filter_map(|gate| match gate {
+                Gate::Range(witness, _) => {
+                    let num_bits = tightest_ranges[&witness];
+                    let implied_by_split =
+                        split_bits.get(&witness).map_or(false, |bit_size| *bit_size <= num_bits);
+                    if implied_by_split || !constrained.insert(witness) {
+                        None
+                    } else {
+                        Some(Gate::Range(witness, num_bits))
+                    }
+                }
+                gate => Some(gate),
+            })
+            .collect()
+    }
+}
+
+/// Returns the number of bits each witness is proven to fit in by a split directive,
+/// along with the constraints on its bits
+fn proven_bit_sizes(gates: &[Gate]) -> BTreeMap<Witness, u32> {
+    let mut booleans = BTreeSet::new();
+    let mut linear_gates: BTreeMap<Witness, Vec<&Expression>> = BTreeMap::new();
+    for gate in gates {
+        if let Gate::Arithmetic(expr) = gate {
+            if let Some(witness) = boolean_witness(expr) {
+                booleans.insert(witness);
+            }
+            if expr.mul_terms.is_empty() {
+                for (_, witness) in &expr.linear_combinations {
+                    linear_gates.entry(*witness).or_default().push(expr);
+                }
+            }
+        }
+    }
+
+    let mut bit_sizes: BTreeMap<Witness, u32> = BTreeMap::new();
+    for gate in gates {
+        if let Gate::Directive(Directive::Split { a, b, bit_size }) = gate {
+            if b.len() != *bit_size as usize || !b.iter().all(|bit| booleans.contains(bit)) {
+                continue;
+            }
+            let recomposed = linear_gates
+                .get(a)
+                .map_or(false, |exprs| exprs.iter().any(|expr| is_recomposition(expr, *a, b)));
+            if recomposed {
+                let size = bit_sizes.entry(*a).or_insert(*bit_size);
+                *size = (*size).min(*bit_size);
+            }
+        }
+    }
+    bit_sizes
+}
+
+/// Returns the witness `x` if the expression is a multiple of `x*x - x`
+fn boolean_witness(expr: &Expression) -> Option<Witness> {
+    match (expr.mul_terms.as_slice(), expr.linear_combinations.as_slice()) {
+        ([(mul_coefficient, lhs, rhs)], [(linear_coefficient, witness)])
+            if lhs == rhs
+                && lhs == witness
+                && !mul_coefficient.is_zero()
+                && *linear_coefficient == -*mul_coefficient
+                && expr.q_c.is_zero() =>
+        {
+            Some(*witness)
+        }
+        _ => None,
+    }
+}
+
+/// Checks whether the linear expression is a multiple of `a - \sum bits[i]*2^i`
+fn is_recomposition(expr: &Expression, a: Witness, bits: &[Witness]) -> bool {
+    if !expr.q_c.is_zero() || expr.linear_combinations.len() != bits.len() + 1 {
+        return false;
+    }
+    let coefficients: BTreeMap<Witness, FieldElement> = expr
+        .linear_combinations
+        .iter()
+        .map(|(coefficient, witness)| (*witness, *coefficient))
+        .collect();
+    // A witness which appears more than once would be missing from the map
+    if coefficients.len() != expr.linear_combinations.len() {
+        return false;
+    }
+    let scale = match coefficients.get(&a) {
+        Some(scale) if !scale.is_zero() => *scale,
+        _ => return false,
+    };
+
+    let two = FieldElement::from(2_i128);
+    let mut power_of_two = FieldElement::one();
+    for bit in bits {
+        if coefficients.get(bit) != Some(&-(scale * power_of_two)) {
+            return false;
+        }
+        power_of_two = power_of_two.mul(two);
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RangeOptimiser;
+    use crate::circuit::{Circuit, Gate};
+    use crate::native_types::Witness;
+
+    fn optimise(text: &str) -> Circuit {
+        let mut circuit = Circuit::from_text(text).unwrap();
+        circuit.gates = RangeOptimiser::optimise(circuit.gates);
+        circuit
+    }
+
+    #[test]
+    fn keeps_the_tightest_range() {
+        let header = "current_witness_index 2\npublic_inputs []\n";
+        let circuit = optimise(&format!(
+            "{}range x1 bits=32\nrange x2 bits=8\nrange x1 bits=16\nrange x1 bits=32\n",
+            header
+        ));
+        let expected =
+            Circuit::from_text(&format!("{}range x1 bits=16\nrange x2 bits=8\n", header)).unwrap();
+        assert_eq!(circuit, expected);
+    }
+
+    #[test]
+    fn removes_ranges_implied_by_splits() {
+        let split = "
+            current_witness_index 4
+            public_inputs []
+            split x1 bits=2 -> [x2 x3]
+            arithmetic 1*x2*x2 + -1*x2 + 0 = 0
+            arithmetic -1*x3*x3 + 1*x3 + 0 = 0
+            arithmetic 1*x1 + -1*x2 + -2*x3 + 0 = 0
+            range x1 bits=8
+            range x1 bits=2
+            range x4 bits=2
+            ";
+        let circuit = optimise(split);
+        assert_eq!(circuit.gates.len(), 5);
+        assert_eq!(circuit.gates[4], Gate::Range(Witness(4), 2));
+
+        // A range which is tighter than the split is kept
+        let circuit = optimise(&split.replace("range x1 bits=8", "range x1 bits=1"));
+        assert_eq!(circuit.gates.len(), 6);
+        assert_eq!(circuit.gates[4], Gate::Range(Witness(1), 1));
+
+        // Without the boolean constraints, or the recomposition, the split proves nothing
+        for unconstrained in
+            &["arithmetic -1*x3*x3 + 1*x3 + 0 = 0", "arithmetic 1*x1 + -1*x2 + -2*x3 + 0 = 0"]
+        {
+            let circuit = optimise(&split.replace(unconstrained, ""));
+            assert_eq!(circuit.gates.len(), 5);
+            assert_eq!(circuit.gates[3], Gate::Range(Witness(1), 2));
+        }
+    }
+}
@@ -0,0 +1,274 @@
+// Removes the gates which do not affect the rest of the circuit, then renumbers the witnesses
+// which are still used so that there are no gaps between their indices.
+//
+// A directive is removed when none of its outputs are read by another gate, as it then only
+// computes values which are never used.
+//
+// An arithmetic gate is removed when it has a witness which no other gate reads, and which
+// only appears in it as a linear term with a non-zero coefficient. Whatever the values of its other
+// witnesses, the gate can be satisfied by choosing the value of that witness, so it does not constrain them.
+//
+// Removing a gate can leave another gate removable, so this is repeated until nothing changes.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use acir::{
+    circuit::{
+        gate::{AndGate, Directive, GadgetCall, GadgetInput, XorGate},
+        Circuit, Gate, PublicInputs,
+    },
+    native_types::{Expression, Witness},
+};
+
+/// Removes dead gates from the circuit and renumbers its witnesses densely.
+///
+/// The first `num_input_witnesses` witnesses hold the inputs of the program, in the layout described
+/// by its ABI, so they are never removed or renumbered. Public inputs are never removed either,
+/// and they keep their order.
+pub fn compact_witnesses(acir: Circuit, num_input_witnesses: u32) -> Circuit {
+    let is_protected = |witness: &Witness| {
+        witness.witness_index() <= num_input_witnesses || acir.public_inputs.0.contains(witness)
+    };
+
+    let mut reads: BTreeMap<Witness, usize> = BTreeMap::new();
+    for gate in &acir.gates {
+        for witness in read_witnesses(gate) {
+            *reads.entry(witness).or_default() += 1;
+        }
+    }
+
+    let mut alive = vec![true; acir.gates.len()];
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (gate, is_alive) in acir.gates.iter().zip(alive.iter_mut()) {
+            if !*is_alive {
+                continue;
+            }
+            let is_read = |witness: &Witness| reads.get(witness).map_or(false, |count| *count > 0);
+            let dead = match gate {
+                Gate::Directive(directive) => directive_outputs(directive)
+                    .iter()
+                    .all(|witness| !is_protected(witness) && !is_read(witness)),
+                Gate::Arithmetic(expr) => {
+                    expr.linear_combinations.iter().any(|(coefficient, witness)| {
+                        !coefficient.is_zero()
+                            && !is_protected(witness)
+                            && reads.get(witness) == Some(&1)
+                    })
+                }
+                _ => false,
+            };
+            if dead {
+                *is_alive = false;
+                changed = true;
+                for witness in read_witnesses(gate) {
+                    *reads.get_mut(&witness).expect("ice: the witness was counted") -= 1;
+                }
+            }
+        }
+    }
+
+    let gates: Vec<_> = acir
+        .gates
+        .into_iter()
+        .zip(alive)
+        .filter_map(|(gate, is_alive)| if is_alive { Some(gate) } else { None })
+        .collect();
+
+    // Witnesses are renumbered in order, so that the public inputs keep their order
+    let mut used: BTreeSet<Witness> = acir.public_inputs.0.iter().copied().collect();
+    for gate in &gates {
+        used.extend(read_witnesses(gate));
+        if let Gate::Directive(directive) = gate {
+            used.extend(directive_outputs(directive));
+        }
+    }
+    let mut current_witness_index = num_input_witnesses;
+    let mut renumbered = BTreeMap::new();
+    for witness in used.into_iter().filter(|witness| witness.witness_index() > num_input_witnesses)
+    {
+        current_witness_index += 1;
+        renumbered.insert(witness, Witness(current_witness_index));
+    }
+    let renumber = |witness: Witness| renumbered.get(&witness).copied().unwrap_or(witness);
+
+    Circuit {
+        current_witness_index,
+        gates: gates.into_iter().map(|gate| renumber_gate(gate, &renumber)).collect(),
+        public_inputs: PublicInputs(acir.public_inputs.0.into_iter().map(renumber).collect()),
+    }
+}
+
+/// Returns the witnesses whose values a gate depends on, which are all of the witnesses
+/// of a constraint but only the inputs of a directive
+fn read_witnesses(gate: &Gate) -> Vec<Witness> {
+    match gate {
+        Gate::Arithmetic(expr) => expression_witnesses(expr),
+        Gate::Range(witness, _) => vec![*witness],
+        Gate::And(AndGate { a, b, result, .. }) | Gate::Xor(XorGate { a, b, result, .. }) => {
+            vec![*a, *b, *result]
+        }
+        Gate::GadgetCall(GadgetCall { inputs, outputs, .. }) => {
+            inputs.iter().map(|input| input.witness).chain(outputs.iter().copied()).collect()
+        }
+        Gate::Directive(Directive::Invert { x, .. }) => vec![*x],
+        Gate::Directive(Directive::Quotient { a, b, predicate, .. }) => {
+            let mut witnesses = expression_witnesses(a);
+            witnesses.extend(expression_witnesses(b));
+            if let Some(predicate) = predicate {
+                witnesses.extend(expression_witnesses(predicate));
+            }
+            witnesses
+        }
+        Gate::Directive(Directive::Truncate { a, .. })
+        | Gate::Directive(Directive::Oddrange { a, .. })
+        | Gate::Directive(Directive::Split { a, .. }) => vec![*a],
+    }
+}
+
+fn directive_outputs(directive: &Directive) -> Vec<Witness> {
+    match directive {
+        Directive::Invert { result, .. } => vec![*result],
+        Directive::Quotient { q, r, .. } => vec![*q, *r],
+        Directive::Truncate { b, c, .. } => vec![*b, *c],
+        Directive::Oddrange { b, r, .. } => vec![*b, *r],
+        Directive::Split { b, .. } => b.clone(),
+    }
+}
+
+fn expression_witnesses(expr: &Expression) -> Vec<Witness> {
+    expr.mul_terms
+        .iter()
+        .flat_map(|(_, lhs, rhs)| [*lhs, *rhs])
+        .chain(expr.linear_combinations.iter().map(|(_, witness)| *witness))
+        .collect()
+}
+
+fn renumber_gate(gate: Gate, renumber: &impl Fn(Witness) -> Witness) -> Gate {
+    match gate {
+        Gate::Arithmetic(expr) => Gate::Arithmetic(renumber_expression(expr, renumber)),
+        Gate::Range(witness, num_bits) => Gate::Range(renumber(witness), num_bits),
+        Gate::And(AndGate { a, b, result, num_bits }) => Gate::And(AndGate {
+            a: renumber(a),
+            b: renumber(b),
+            result: renumber(result),
+            num_bits,
+        }),
+        Gate::Xor(XorGate { a, b, result, num_bits }) => Gate::Xor(XorGate {
+            a: renumber(a),
+            b: renumber(b),
+            result: renumber(result),
+            num_bits,
+        }),
+        Gate::GadgetCall(GadgetCall { name, inputs, outputs }) => Gate::GadgetCall(GadgetCall {
+            name,
+            inputs: inputs
+                .into_iter()
+                .map(|input| GadgetInput { witness: renumber(input.witness), ..input })
+                .collect(),
+            outputs: outputs.into_iter().map(renumber).collect(),
+        }),
+        Gate::Directive(directive) => Gate::Directive(match directive {
+            Directive::Invert { x, result } => {
+                Directive::Invert { x: renumber(x), result: renumber(result) }
+            }
+            Directive::Quotient { a, b, q, r, predicate } => Directive::Quotient {
+                a: renumber_expression(a, renumber),
+                b: renumber_expression(b, renumber),
+                q: renumber(q),
+                r: renumber(r),
+                predicate: predicate
+                    .map(|predicate| Box::new(renumber_expression(*predicate, renumber))),
+            },
+            Directive::Truncate { a, b, c, bit_size } => {
+                Directive::Truncate { a: renumber(a), b: renumber(b), c: renumber(c), bit_size }
+            }
+            Directive::Oddrange { a, b, r, bit_size } => {
+                Directive::Oddrange { a: renumber(a), b: renumber(b), r: renumber(r), bit_size }
+            }
+            Directive::Split { a, b, bit_size } => Directive::Split {
+                a: renumber(a),
+                b: b.into_iter().map(renumber).collect(),
+                bit_size,
+            },
+        }),
+    }
+}
+
+fn renumber_expression(expr: Expression, renumber: &impl Fn(Witness) -> Witness) -> Expression {
+    Expression {
+        mul_terms: expr
+            .mul_terms
+            .into_iter()
+            .map(|(coefficient, lhs, rhs)| (coefficient, renumber(lhs), renumber(rhs)))
+            .collect(),
+        linear_combinations: expr
+            .linear_combinations
+            .into_iter()
+            .map(|(coefficient, witness)| (coefficient, renumber(witness)))
+            .collect(),
+        q_c: expr.q_c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use acir::circuit::Circuit;
+
+    use super::compact_witnesses;
+
+    #[test]
+    fn removes_dead_gates_and_renumbers_witnesses() {
+        let circuit = Circuit::from_text(
+            "
+            current_witness_index 12
+            public_inputs [x2 x3]
+            // The inputs are x1 to x4, x4 is not used but it must keep its index
+            arithmetic 1*x1*x2 + -1*x6 + 0 = 0
+            range x6 bits=8
+            // x7 is not read by any gate
+            invert x1 -> x7
+            // x9 only defines x10, which is not read by any gate
+            arithmetic 1*x3 + -1*x9 + 0 = 0
+            arithmetic 1*x9 + -1*x10 + 1 = 0
+            // x11 is used in a multiplication, so this constrains x1
+            invert x1 -> x11
+            arithmetic 1*x1*x11 + -1 = 0
+            arithmetic 1*x1 + -1*x3 + 0 = 0
+            ",
+        )
+        .unwrap();
+
+        let expected = Circuit::from_text(
+            "
+            current_witness_index 6
+            public_inputs [x2 x3]
+            arithmetic 1*x1*x2 + -1*x5 + 0 = 0
+            range x5 bits=8
+            invert x1 -> x6
+            arithmetic 1*x1*x6 + -1 = 0
+            arithmetic 1*x1 + -1*x3 + 0 = 0
+            ",
+        )
+        .unwrap();
+
+        assert_eq!(compact_witnesses(circuit, 4), expected);
+    }
+
+    #[test]
+    fn public_inputs_keep_their_order() {
+        let circuit = Circuit::from_text(
+            "
+            current_witness_index 9
+            public_inputs [x9 x5]
+            arithmetic 1*x1*x5 + -1*x9 + 0 = 0
+            ",
+        )
+        .unwrap();
+
+        let compacted = compact_witnesses(circuit, 1);
+        assert_eq!(compacted.current_witness_index, 3);
+        assert_eq!(compacted.public_inputs.indices(), vec![3, 2]);
+    }
+}
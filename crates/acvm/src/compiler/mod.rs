@@ -0,0 +1,177 @@
+mod compaction;
+
+use indexmap::IndexMap;
+
+use crate::Language;
+use acir::{
+    circuit::{Circuit, Gate},
+    native_types::{Expression, Witness},
+    optimiser::{CSatOptimiser, GeneralOptimiser, RangeOptimiser},
+};
+
+pub use compaction::compact_witnesses;
+
+/// The optimisations which `compile_with_options` applies, in addition to those the language requires
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompileOptions {
+    /// Removes the range constraints which are implied by other constraints
+    pub optimise_ranges: bool,
+}
+
+impl Default for CompileOptions {
+    fn default() -> Self {
+        CompileOptions { optimise_ranges: true }
+    }
+}
+
+pub fn compile(acir: Circuit, np_language: Language) -> Circuit {
+    compile_with_options(acir, np_language, CompileOptions::default()).0
+}
+
+/// Returns the compiled circuit, along with the number of range constraints which the range
+/// optimiser removed from it
+pub fn compile_with_options(
+    acir: Circuit,
+    np_language: Language,
+    options: CompileOptions,
+) -> (Circuit, usize) {
+    #[cfg(debug_assertions)]
+    check_well_formed(&acir);
+
+    // The ranges are optimised first, as the optimisers for the language can split up
+    // the bit decompositions which the range optimiser looks for
+    let num_gates = acir.gates.len();
+    let acir = if options.optimise_ranges {
+        Circuit { gates: RangeOptimiser::optimise(acir.gates), ..acir }
+    } else {
+        acir
+    };
+    let removed_range_gates = num_gates - acir.gates.len();
+
+    // Instantiate the optimiser.
+    // Currently the optimiser and reducer are one in the same
+    // for CSAT
+    let optimiser = match np_language {
+        crate::Language::R1CS => return (optimise_r1cs(acir), removed_range_gates),
+        crate::Language::PLONKCSat { width } => CSatOptimiser::new(width),
+    };
+
+    // Optimise the arithmetic gates by reducing them into the correct width and
+    // creating intermediate variables when necessary
+    let mut optimised_gates = Vec::new();
+
+    let mut next_witness_index = acir.current_witness_index + 1;
+    for gate in acir.gates {
+        match gate {
+            Gate::Arithmetic(arith_expr) => {
+                let mut intermediate_variables: IndexMap<Witness, Expression> = IndexMap::new();
+
+                let arith_expr =
+                    optimiser.optimise(arith_expr, &mut intermediate_variables, next_witness_index);
+
+                // Update next_witness counter
+                next_witness_index += intermediate_variables.len() as u32;
+                let mut new_gates = Vec::new();
+                for (_, mut g) in intermediate_variables {
+                    g.sort();
+                    new_gates.push(g);
+                }
+                new_gates.push(arith_expr);
+                new_gates.sort();
+                for gate in new_gates {
+                    optimised_gates.push(Gate::Arithmetic(gate));
+                }
+            }
+            other_gate => optimised_gates.push(other_gate),
+        }
+    }
+
+    let current_witness_index = next_witness_index - 1;
+
+    let circuit = Circuit {
+        current_witness_index,
+        gates: optimised_gates,
+        public_inputs: acir.public_inputs, // The optimiser does not add public inputs
+    };
+    (circuit, removed_range_gates)
+}
+
+/// Panics if the compiler produced a malformed circuit, before a backend is given it
+#[cfg(debug_assertions)]
+fn check_well_formed(acir: &Circuit) {
+    use acir::circuit::CircuitViolation;
+
+    // Programs may ignore some of their public parameters, which still become public inputs
+    let violations: Vec<_> = acir
+        .validate()
+        .into_iter()
+        .filter(|violation| !matches!(violation, CircuitViolation::UnusedPublicInput { .. }))
+        .map(|violation| violation.to_string())
+        .collect();
+    assert!(
+        violations.is_empty(),
+        "ice: the circuit is malformed:\n    {}",
+        violations.join("\n    ")
+    );
+}
+
+// R1CS optimisations uses the general optimiser.
+// Once R1CS specific optimisations are found, then we can
+// refactor this function
+fn optimise_r1cs(acir: Circuit) -> Circuit {
+    let optimised_arith_gates: Vec<_> = acir
+        .gates
+        .into_iter()
+        .map(|gate| match gate {
+            Gate::Arithmetic(arith) => Gate::Arithmetic(GeneralOptimiser::optimise(arith)),
+            other_gates => other_gates,
+        })
+        .collect();
+
+    Circuit {
+        // The general optimiser may remove enough gates that a witness is no longer used
+        // however, we cannot decrement the number of witnesses, as that
+        // would require a linear scan over all gates in order to decrement all witness indices
+        // above the witness which was removed. `compact_witnesses` does this when it is requested
+        current_witness_index: acir.current_witness_index,
+        gates: optimised_arith_gates,
+        public_inputs: acir.public_inputs,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use acir::circuit::{Circuit, Gate};
+
+    use super::{compile_with_options, CompileOptions};
+    use crate::Language;
+
+    #[test]
+    fn counts_the_range_gates_removed_by_the_range_optimiser() {
+        let circuit = Circuit::from_text(
+            "
+            current_witness_index 2
+            public_inputs [x1]
+            arithmetic 1*x1 + -1*x2 + 0 = 0
+            range x2 bits=8
+            range x2 bits=16
+            ",
+        )
+        .unwrap();
+        let language = || Language::PLONKCSat { width: 3 };
+        let num_ranges = |circuit: &Circuit| {
+            circuit.gates.iter().filter(|gate| matches!(gate, Gate::Range(..))).count()
+        };
+
+        let (optimised, removed) = compile_with_options(
+            circuit.clone(),
+            language(),
+            CompileOptions { optimise_ranges: true },
+        );
+        assert_eq!((num_ranges(&optimised), removed), (1, 1));
+
+        let (unoptimised, removed) =
+            compile_with_options(circuit, language(), CompileOptions { optimise_ranges: false });
+        assert_eq!((num_ranges(&unoptimised), removed), (2, 0));
+    }
+}
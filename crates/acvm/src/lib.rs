@@ -287,10 +287,13 @@ pub enum Language {
     PLONKCSat { width: usize },
 }
 
-pub fn hash_constraint_system(cs: &Circuit) {
+/// Returns a digest of the constraint system.
+///
+/// The digest is computed from the serialized circuit, so it is the same for
+/// equal circuits across runs and machines.
+pub fn hash_constraint_system(cs: &Circuit) -> [u8; 32] {
     use sha2::{Digest, Sha256};
     let mut hasher = Sha256::new();
-    hasher.update(&format!("{:?}", cs));
-    let result = hasher.finalize();
-    println!("hash of constraint system : {:x?}", &result[..]);
+    hasher.update(&cs.to_bytes());
+    hasher.finalize().into()
 }
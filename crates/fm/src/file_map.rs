@@ -1,5 +1,5 @@
 use codespan_reporting::files::{SimpleFile, SimpleFiles};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::FileManager;
 
@@ -77,4 +77,17 @@ impl FileManager {
     pub fn as_simple_files(&self) -> &SimpleFiles<PathString, String> {
         &self.file_map.0
     }
+
+    /// Returns the path and source of every file which was loaded, in the order they were loaded
+    pub fn loaded_files(&self) -> Vec<(&Path, &str)> {
+        let mut file_ids: Vec<_> = self.id_to_path.keys().copied().collect();
+        file_ids.sort_by_key(FileId::as_usize);
+        file_ids
+            .into_iter()
+            .map(|file_id| {
+                let file = self.file_map.0.get(file_id.0).expect("ice: file ids are always valid");
+                (file.name().0.as_path(), file.source().as_str())
+            })
+            .collect()
+    }
 }
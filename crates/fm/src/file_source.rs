@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use crate::file_reader;
+
+/// Provides the sources of the files which are loaded by a [`FileManager`][crate::FileManager].
+///
+/// Modules are resolved by their path in the same way regardless of the source,
+/// so a source only has to be able to read the file at a given path.
+pub trait FileSource: std::fmt::Debug {
+    fn read_file(&self, path_to_file: &Path) -> Result<String, Error>;
+}
+
+/// Reads files from the file system, or using the javascript host function when compiled to wasm
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FileSystem;
+
+impl FileSource for FileSystem {
+    fn read_file(&self, path_to_file: &Path) -> Result<String, Error> {
+        file_reader::read_file_to_string(path_to_file)
+    }
+}
+
+/// Files which are held in memory rather than on the file system, by their path.
+///
+/// This allows programs to be compiled from strings, such as the unsaved buffers of an editor.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct InMemoryFileSource {
+    files: HashMap<PathBuf, String>,
+}
+
+impl InMemoryFileSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the source of the file at `path_to_file`, replacing any source it had before
+    pub fn add_file<P: Into<PathBuf>, S: Into<String>>(&mut self, path_to_file: P, source: S) {
+        self.files.insert(path_to_file.into(), source.into());
+    }
+
+    pub fn contains(&self, path_to_file: &Path) -> bool {
+        self.files.contains_key(path_to_file)
+    }
+}
+
+impl FileSource for InMemoryFileSource {
+    fn read_file(&self, path_to_file: &Path) -> Result<String, Error> {
+        self.files.get(path_to_file).cloned().ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                format!("{} is not in the in-memory file source", path_to_file.display()),
+            )
+        })
+    }
+}
+
+impl From<HashMap<PathBuf, String>> for InMemoryFileSource {
+    fn from(files: HashMap<PathBuf, String>) -> Self {
+        InMemoryFileSource { files }
+    }
+}
+
+impl<P: Into<PathBuf>, S: Into<String>> std::iter::FromIterator<(P, S)> for InMemoryFileSource {
+    fn from_iter<I: IntoIterator<Item = (P, S)>>(iter: I) -> Self {
+        let mut source = InMemoryFileSource::new();
+        for (path_to_file, contents) in iter {
+            source.add_file(path_to_file, contents);
+        }
+        source
+    }
+}
@@ -1,7 +1,9 @@
 mod file_map;
 mod file_reader;
+mod file_source;
 
 pub use file_map::{File, FileId, FileMap};
+pub use file_source::{FileSource, FileSystem, InMemoryFileSource};
 
 pub mod util;
 use std::{
@@ -12,7 +14,6 @@ pub use util::*;
 
 pub const FILE_EXTENSION: &str = "nr";
 
-// XXX: Create a trait for file io
 /// An enum to differentiate between the root file
 /// which the compiler starts at, and the others.
 /// This is so that submodules of the root, can live alongside the
@@ -30,28 +31,48 @@ pub struct FileManager {
     file_map: file_map::FileMap,
     id_to_path: HashMap<FileId, VirtualPath>,
     path_to_id: HashMap<VirtualPath, FileId>,
+    file_source: Box<dyn FileSource>,
+    // The sources of files which are not in the file source, such as a standard library
+    // embedded in a binary. These are used instead of reading the file at the same path.
+    embedded_files: InMemoryFileSource,
 }
 
 impl FileManager {
+    /// Creates a FileManager which reads files from the file system
     pub fn new() -> Self {
+        Self::with_file_source(Box::new(FileSystem))
+    }
+
+    /// Creates a FileManager which reads files from the given source
+    pub fn with_file_source(file_source: Box<dyn FileSource>) -> Self {
         Self {
             file_map: file_map::FileMap::new(),
             id_to_path: HashMap::new(),
             path_to_id: HashMap::new(),
+            file_source,
+            embedded_files: InMemoryFileSource::new(),
         }
     }
 
+    /// Provides the source of the file at `path_to_file`, so that it is not read from the file source.
+    ///
+    /// The file is only loaded once it is added, or resolved as a module, like any other file.
+    pub fn add_embedded_file(&mut self, path_to_file: PathBuf, source: String) {
+        self.embedded_files.add_file(path_to_file, source);
+    }
+
     // XXX: Maybe use a AsRef<Path> here, for API ergonomics
     pub fn add_file(&mut self, path_to_file: &Path, file_type: FileType) -> Option<FileId> {
-        // We expect the caller to ensure that the file is a valid noir file
-        let ext = path_to_file
-            .extension()
-            .unwrap_or_else(|| panic!("{:?} does not have an extension", path_to_file));
-        if ext != FILE_EXTENSION {
+        // Only noir files can be added
+        if path_to_file.extension()? != FILE_EXTENSION {
             return None;
         }
 
-        let source = file_reader::read_file_to_string(path_to_file).ok()?;
+        let source = if self.embedded_files.contains(path_to_file) {
+            self.embedded_files.read_file(path_to_file).ok()?
+        } else {
+            self.file_source.read_file(path_to_file).ok()?
+        };
 
         let file_id = self.file_map.add_file(path_to_file.to_path_buf().into(), source);
         let path_to_file = virtualise_path(path_to_file, file_type);
@@ -135,6 +156,41 @@ mod tests {
         file_path
     }
 
+    #[test]
+    fn path_resolve_embedded_module() {
+        let mut fm = FileManager::new();
+        let root = PathBuf::from("<embedded>");
+        fm.add_embedded_file(root.join("lib.nr"), "mod foo;".to_owned());
+        fm.add_embedded_file(root.join("foo.nr"), "fn bar() {}".to_owned());
+
+        let file_id = fm.add_file(&root.join("lib.nr"), FileType::Root).unwrap();
+        let foo_id = fm.resolve_path(file_id, "foo").unwrap();
+        assert_eq!(fm.fetch_file(foo_id).get_source(), "fn bar() {}");
+        assert!(fm.resolve_path(file_id, "baz").is_err());
+    }
+
+    #[test]
+    fn path_resolve_in_memory_module() {
+        let files: InMemoryFileSource = vec![
+            ("src/main.nr", "mod foo;"),
+            ("src/foo.nr", "mod bar;"),
+            ("src/foo/bar.nr", "fn baz() {}"),
+            ("src/README", "not a noir file"),
+        ]
+        .into_iter()
+        .collect();
+        let mut fm = FileManager::with_file_source(Box::new(files));
+
+        let file_id = fm.add_file(Path::new("src/main.nr"), FileType::Root).unwrap();
+        let foo_id = fm.resolve_path(file_id, "foo").unwrap();
+        let bar_id = fm.resolve_path(foo_id, "bar").unwrap();
+        assert_eq!(fm.fetch_file(bar_id).get_source(), "fn baz() {}");
+
+        assert!(fm.resolve_path(file_id, "bar").is_err());
+        assert!(fm.add_file(Path::new("src/README"), FileType::Normal).is_none());
+        assert!(fm.add_file(Path::new("src/missing.nr"), FileType::Normal).is_none());
+    }
+
     #[test]
     fn path_resolve_file_module() {
         let dir = tempdir().unwrap();
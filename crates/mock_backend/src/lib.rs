@@ -0,0 +1,215 @@
+// A backend which does not create real proofs.
+//
+// Proving checks every gate of the circuit against the witness, and the proof is a digest
+// of the circuit and its public inputs. Verifying recomputes this digest, so a proof only
+// verifies for the circuit and public inputs it was created with.
+//
+// This allows nargo to be built and tested without a proving system,
+// it must never be used to prove anything in production.
+
+use std::collections::BTreeMap;
+
+use acvm::acir::circuit::gate::GadgetCall;
+use acvm::acir::circuit::{Circuit, Gate};
+use acvm::acir::native_types::Witness;
+use acvm::acir::OPCODE;
+use acvm::pwg::{hash, signature};
+use acvm::{
+    Backend, FieldElement, Language, PartialWitnessGenerator, ProofSystemCompiler, SmartContract,
+};
+use sha2::{Digest, Sha256};
+
+/// Separates the transcript of the mock backend from any other use of the hash
+const TRANSCRIPT_DOMAIN: &[u8] = b"noir_mock_backend_proof";
+
+pub struct Mock;
+
+impl Backend for Mock {}
+
+impl PartialWitnessGenerator for Mock {
+    fn solve_gadget_call(
+        initial_witness: &mut BTreeMap<Witness, FieldElement>,
+        gc: &GadgetCall,
+    ) -> Result<(), OPCODE> {
+        match gc.name {
+            OPCODE::SHA256 => hash::sha256(initial_witness, gc),
+            OPCODE::Blake2s => hash::blake2s(initial_witness, gc),
+            OPCODE::EcdsaSecp256k1 => signature::ecdsa::secp256k1_prehashed(initial_witness, gc),
+            OPCODE::AES
+            | OPCODE::MerkleMembership
+            | OPCODE::SchnorrVerify
+            | OPCODE::Pedersen
+            | OPCODE::HashToField
+            | OPCODE::FixedBaseScalarMul
+            | OPCODE::ToBits => return Err(gc.name),
+        }
+        Ok(())
+    }
+}
+
+impl ProofSystemCompiler for Mock {
+    fn np_language(&self) -> Language {
+        Language::PLONKCSat { width: 3 }
+    }
+
+    /// Returns an empty proof, which never verifies, if the witness does not satisfy the circuit
+    fn prove_with_meta(
+        &self,
+        circuit: Circuit,
+        witness_values: BTreeMap<Witness, FieldElement>,
+    ) -> Vec<u8> {
+        if !circuit.gates.iter().all(|gate| is_satisfied(gate, &witness_values)) {
+            return Vec::new();
+        }
+
+        let public_inputs: Option<Vec<_>> = circuit
+            .public_inputs
+            .0
+            .iter()
+            .map(|witness| witness_values.get(witness).copied())
+            .collect();
+        match public_inputs {
+            Some(public_inputs) => transcript(&circuit, &public_inputs),
+            None => Vec::new(),
+        }
+    }
+
+    fn verify_from_cs(
+        &self,
+        proof: &[u8],
+        public_inputs: Vec<FieldElement>,
+        circuit: Circuit,
+    ) -> bool {
+        public_inputs.len() == circuit.public_inputs.0.len()
+            && !proof.is_empty()
+            && proof == transcript(&circuit, &public_inputs).as_slice()
+    }
+}
+
+impl SmartContract for Mock {
+    fn eth_contract_from_cs(&self, circuit: Circuit) -> String {
+        let circuit_hash = Sha256::digest(&circuit.to_bytes());
+        format!(
+            r#"// SPDX-License-Identifier: UNLICENSED
+// Generated by the mock backend of nargo, this contract cannot verify proofs.
+pragma solidity >=0.6.0;
+
+contract MockVerifier {{
+    bytes32 public constant CIRCUIT_HASH = 0x{:x};
+
+    function verify(bytes calldata) external pure returns (bool) {{
+        revert("proofs of the mock backend cannot be verified on chain");
+    }}
+}}
+"#,
+            circuit_hash
+        )
+    }
+}
+
+/// Computes the proof of the circuit for the given public inputs
+fn transcript(circuit: &Circuit, public_inputs: &[FieldElement]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(TRANSCRIPT_DOMAIN);
+    hasher.update(&circuit.to_bytes());
+    for public_input in public_inputs {
+        hasher.update(&public_input.to_bytes());
+    }
+    hasher.finalize().to_vec()
+}
+
+/// Checks a gate against the witness. Missing witness values never satisfy a gate.
+fn is_satisfied(gate: &Gate, witness: &BTreeMap<Witness, FieldElement>) -> bool {
+    match gate {
+        Gate::Arithmetic(expr) => Mock::get_value(expr, witness).map_or(false, |v| v.is_zero()),
+        Gate::Range(w, num_bits) => witness.get(w).map_or(false, |v| v.num_bits() <= *num_bits),
+        Gate::And(and_gate) => {
+            match (
+                witness.get(&and_gate.a),
+                witness.get(&and_gate.b),
+                witness.get(&and_gate.result),
+            ) {
+                (Some(a), Some(b), Some(result)) => a.and(b, and_gate.num_bits) == *result,
+                _ => false,
+            }
+        }
+        Gate::Xor(xor_gate) => {
+            match (
+                witness.get(&xor_gate.a),
+                witness.get(&xor_gate.b),
+                witness.get(&xor_gate.result),
+            ) {
+                (Some(a), Some(b), Some(result)) => a.xor(b, xor_gate.num_bits) == *result,
+                _ => false,
+            }
+        }
+        Gate::GadgetCall(gc) => {
+            // Solve the gadget call again from its inputs alone, and compare the outputs
+            let mut inputs = BTreeMap::new();
+            for input in &gc.inputs {
+                match witness.get(&input.witness) {
+                    Some(value) => inputs.insert(input.witness, *value),
+                    None => return false,
+                };
+            }
+            if Mock::solve_gadget_call(&mut inputs, gc).is_err() {
+                return false;
+            }
+            gc.outputs.iter().all(|output| {
+                matches!((inputs.get(output), witness.get(output)), (Some(a), Some(b)) if a == b)
+            })
+        }
+        // Directives only compute witness values, they do not constrain them
+        Gate::Directive(_) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use acvm::acir::circuit::{Circuit, Gate, PublicInputs};
+    use acvm::acir::native_types::{Expression, Witness};
+    use acvm::{FieldElement, PartialWitnessGenerator, ProofSystemCompiler};
+
+    use super::Mock;
+
+    /// A circuit proving knowledge of `x` such that `x * x = y`, where `y` is public
+    fn square_circuit() -> Circuit {
+        let x = Witness(1);
+        let y = Witness(2);
+        let gate = Expression {
+            mul_terms: vec![(FieldElement::one(), x, x)],
+            linear_combinations: vec![(-FieldElement::one(), y)],
+            q_c: FieldElement::zero(),
+        };
+        Circuit {
+            current_witness_index: 2,
+            gates: vec![Gate::Arithmetic(gate), Gate::Range(x, 8)],
+            public_inputs: PublicInputs(vec![y]),
+        }
+    }
+
+    #[test]
+    fn proves_and_verifies() {
+        let circuit = square_circuit();
+        let mut witness = BTreeMap::from([(Witness(1), FieldElement::from(3_i128))]);
+        assert_eq!(Mock.solve(&mut witness, circuit.gates.clone()), acvm::GateResolution::Resolved);
+
+        let proof = Mock.prove_with_meta(circuit.clone(), witness);
+        assert!(Mock.verify_from_cs(&proof, vec![FieldElement::from(9_i128)], circuit.clone()));
+        assert!(!Mock.verify_from_cs(&proof, vec![FieldElement::from(10_i128)], circuit));
+    }
+
+    #[test]
+    fn unsatisfied_circuits_do_not_verify() {
+        let circuit = square_circuit();
+        let witness = BTreeMap::from([
+            (Witness(1), FieldElement::from(3_i128)),
+            (Witness(2), FieldElement::from(10_i128)),
+        ]);
+
+        let proof = Mock.prove_with_meta(circuit.clone(), witness);
+        assert!(!Mock.verify_from_cs(&proof, vec![FieldElement::from(10_i128)], circuit));
+    }
+}
@@ -1,13 +1,27 @@
-use std::fs;
+use sha2::{Digest, Sha256};
+use std::fmt::Write;
 use std::path::{Path, PathBuf};
 
+/// The crates whose sources determine the programs which nargo compiles
+const COMPILER_CRATES: &[&str] = &[
+    "fm",
+    "noir_field",
+    "noirc_abi",
+    "noirc_driver",
+    "noirc_errors",
+    "noirc_evaluator",
+    "noirc_frontend",
+];
+
 /// Expects that the given directory is an existing path
-fn rerun_if_stdlib_changes(directory: &Path) {
+fn rerun_if_changed(directory: &Path) {
+    // Directories are watched as well, so that new files are embedded
+    println!("cargo:rerun-if-changed={}", directory.to_string_lossy());
     for entry in std::fs::read_dir(directory).unwrap() {
         let path = entry.unwrap().path();
 
         if path.is_dir() {
-            rerun_if_stdlib_changes(&path);
+            rerun_if_changed(&path);
         } else {
             // Tell Cargo that if the given file changes, to rerun this build script.
             println!("cargo:rerun-if-changed={}", path.to_string_lossy());
@@ -15,56 +29,67 @@ fn rerun_if_stdlib_changes(directory: &Path) {
     }
 }
 
-pub fn copy<U: AsRef<Path>, V: AsRef<Path>>(from: U, to: V) -> Result<(), std::io::Error> {
-    let mut stack = vec![PathBuf::from(from.as_ref())];
-
-    let output_root = PathBuf::from(to.as_ref());
-    let input_root = PathBuf::from(from.as_ref()).components().count();
-
-    while let Some(working_path) = stack.pop() {
-        println!("process: {:?}", &working_path);
-
-        // Generate a relative path
-        let src: PathBuf = working_path.components().skip(input_root).collect();
+/// Collects every file in `directory`, along with its path relative to `root` using `/` separators
+fn collect_files(root: &Path, directory: &Path, files: &mut Vec<(String, PathBuf)>) {
+    for entry in std::fs::read_dir(directory).unwrap() {
+        let path = entry.unwrap().path();
 
-        // Create a destination if missing
-        let dest = if src.components().count() == 0 {
-            output_root.clone()
+        if path.is_dir() {
+            collect_files(root, &path, files);
         } else {
-            output_root.join(&src)
-        };
-        if fs::metadata(&dest).is_err() {
-            println!(" mkdir: {:?}", dest);
-            fs::create_dir_all(&dest)?;
+            let relative_path: Vec<_> = path
+                .strip_prefix(root)
+                .unwrap()
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy().into_owned())
+                .collect();
+            files.push((relative_path.join("/"), path.canonicalize().unwrap()));
         }
+    }
+}
 
-        for entry in fs::read_dir(working_path)? {
-            let entry = entry?;
-            let path = entry.path();
+/// Hashes the manifests and sources of the compiler crates, so that the build cache can tell
+/// apart the programs compiled by different builds of the compiler with the same version
+fn compiler_hash() -> String {
+    let mut hasher = Sha256::new();
+    for name in COMPILER_CRATES {
+        let crate_dir = Path::new("..").join(name);
+        let src_dir = crate_dir.join("src");
+        rerun_if_changed(&src_dir);
+        println!("cargo:rerun-if-changed={}", crate_dir.join("Cargo.toml").to_string_lossy());
 
-            if path.is_dir() {
-                stack.push(path);
-            } else {
-                match path.file_name() {
-                    Some(filename) => {
-                        let dest_path = dest.join(filename);
-                        println!("  copy: {:?} -> {:?}", &path, &dest_path);
-                        fs::copy(&path, &dest_path)?;
-                    }
-                    None => {
-                        println!("failed: {:?}", path);
-                    }
-                }
-            }
+        let mut files = vec![("Cargo.toml".to_owned(), crate_dir.join("Cargo.toml"))];
+        collect_files(&src_dir, &src_dir, &mut files);
+        files.sort();
+        for (relative_path, path) in files {
+            let contents = std::fs::read(&path).unwrap();
+            hasher.update(format!("{}/{}", name, relative_path).as_bytes());
+            hasher.update(&[0]);
+            hasher.update(&(contents.len() as u64).to_be_bytes());
+            hasher.update(&contents);
         }
     }
-
-    Ok(())
+    format!("{:x}", hasher.finalize())
 }
 
+/// Embeds the sources of the standard library into nargo,
+/// so that nargo does not depend on a copy of the standard library being installed
 fn main() {
-    let stdlib_src_dir = Path::new("../../noir_stdlib/");
-    rerun_if_stdlib_changes(stdlib_src_dir);
-    let target = dirs::config_dir().unwrap().join("noir-lang").join("std");
-    copy(stdlib_src_dir, &target).unwrap();
+    println!("cargo:rustc-env=NARGO_COMPILER_HASH={}", compiler_hash());
+
+    let stdlib_src_dir = Path::new("../../noir_stdlib/src");
+    rerun_if_changed(stdlib_src_dir);
+
+    let mut files = Vec::new();
+    collect_files(stdlib_src_dir, stdlib_src_dir, &mut files);
+    files.sort();
+
+    let mut generated = String::from("&[\n");
+    for (relative_path, path) in files {
+        writeln!(generated, "    ({:?}, include_str!({:?})),", relative_path, path).unwrap();
+    }
+    generated.push(']');
+
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR").unwrap());
+    std::fs::write(out_dir.join("stdlib.rs"), generated).unwrap();
 }
@@ -0,0 +1,45 @@
+use std::path::Path;
+
+use acvm::acir::circuit::Circuit;
+use noirc_abi::Abi;
+use serde_derive::{Deserialize, Serialize};
+
+use crate::errors::CliError;
+
+/// A self-contained description of a compiled program.
+///
+/// This contains everything that is needed to verify a proof for the program,
+/// so that a verifier does not need access to the source code or its dependencies.
+///
+/// Note: The backends currently verify proofs from the constraint system directly,
+/// so the circuit also serves as the backend's verification data.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct ProgramArtifact {
+    /// The name of the backend the circuit was compiled for, as its gates depend on the backend
+    pub backend: String,
+    pub circuit: Circuit,
+    /// The parameters of `main`, with their types and visibility
+    pub abi: Abi,
+}
+
+impl ProgramArtifact {
+    pub fn new(backend: &str, circuit: Circuit, abi: Abi) -> ProgramArtifact {
+        ProgramArtifact { backend: backend.to_owned(), circuit, abi }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("ice: could not serialize the program artifact")
+    }
+
+    /// Reads an artifact written by `nargo compile` from disk
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<ProgramArtifact, CliError> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path).map_err(|err| {
+            CliError::Generic(format!("cannot read artifact at {}: {}", path.display(), err))
+        })?;
+
+        serde_json::from_str(&json).map_err(|err| {
+            CliError::Generic(format!("artifact at {} is badly formed: {}", path.display(), err))
+        })
+    }
+}
@@ -1,15 +1,232 @@
-cfg_if::cfg_if! {
-    if #[cfg(feature = "plonk_bn254")] {
+use std::{collections::BTreeMap, path::Path};
 
-        pub use aztec_backend::Plonk as ConcreteBackend;
+use acvm::acir::circuit::{Circuit, Gate};
+use acvm::acir::native_types::Witness;
+use acvm::{
+    FieldElement, GateResolution, Language, PartialWitnessGenerator, ProofSystemCompiler,
+    SmartContract,
+};
 
-    } else if #[cfg(feature = "marlin")] {
-        // R1CS_MARLIN_ARKWORKS
-        pub use marlin_arkworks_backend::Marlin as ConcreteBackend;
-    } else {
-        compile_error!("please specify a backend to compile with");
+use crate::errors::CliError;
+
+// nargo is compiled for a single field, so only the backends which use that field can be
+// compiled in together. One of them is then chosen for each package.
+//
+// This means that plonk and mock, which use bn254, can never be chosen between with marlin,
+// which uses bls12_381. A nargo built with `--no-default-features --features marlin` is needed for marlin.
+#[cfg(not(any(feature = "plonk_bn254", feature = "marlin", feature = "mock")))]
+compile_error!("please specify a backend to compile with");
+
+#[cfg(all(feature = "marlin", any(feature = "plonk_bn254", feature = "mock")))]
+compile_error!(
+    "the `marlin` feature uses the bls12_381 field, so it cannot be used together with `plonk_bn254` or `mock`, which use bn254"
+);
+
+/// The names of all of the backends nargo supports, along with the feature which enables each one
+/// and the field it uses
+const KNOWN_BACKENDS: [(&str, &str, &str); 3] = [
+    ("plonk", "plonk_bn254", "bn254"),
+    ("marlin", "marlin", "bls12_381"),
+    ("mock", "mock", "bn254"),
+];
+
+/// A backend which was compiled into nargo
+pub enum ConcreteBackend {
+    #[cfg(feature = "plonk_bn254")]
+    Plonk(aztec_backend::Plonk),
+    // R1CS_MARLIN_ARKWORKS
+    #[cfg(feature = "marlin")]
+    Marlin(marlin_arkworks_backend::Marlin),
+    // Checks the witness against the circuit without creating a real proof
+    #[cfg(feature = "mock")]
+    Mock(mock_backend::Mock),
+}
+
+/// Calls the same method on whichever backend was chosen
+macro_rules! dispatch {
+    ($backend:expr, $inner:ident => $call:expr) => {
+        match $backend {
+            #[cfg(feature = "plonk_bn254")]
+            ConcreteBackend::Plonk($inner) => $call,
+            #[cfg(feature = "marlin")]
+            ConcreteBackend::Marlin($inner) => $call,
+            #[cfg(feature = "mock")]
+            ConcreteBackend::Mock($inner) => $call,
+        }
+    };
+}
+
+impl ConcreteBackend {
+    /// Returns the backend with the given name, or an error if nargo was built without it
+    pub fn from_name(name: &str) -> Result<ConcreteBackend, CliError> {
+        match name {
+            #[cfg(feature = "plonk_bn254")]
+            "plonk" => return Ok(ConcreteBackend::Plonk(aztec_backend::Plonk)),
+            #[cfg(feature = "marlin")]
+            "marlin" => return Ok(ConcreteBackend::Marlin(marlin_arkworks_backend::Marlin)),
+            #[cfg(feature = "mock")]
+            "mock" => return Ok(ConcreteBackend::Mock(mock_backend::Mock)),
+            _ => (),
+        }
+
+        let available = available_backends().join(", ");
+        match KNOWN_BACKENDS.iter().find(|(known, ..)| *known == name) {
+            Some((_, feature, field)) => Err(CliError::Generic(format!(
+                "the `{}` backend is not available as nargo was built without the `{}` feature, the available backends are: {}\n\
+                 Only backends which use the same field can be built into nargo together, `{}` uses {}",
+                name, feature, available, name, field
+            ))),
+            None => Err(CliError::Generic(format!(
+                "unknown backend `{}`, the available backends are: {}",
+                name, available
+            ))),
+        }
+    }
+
+    /// Returns the backend used when a package does not choose one
+    pub fn default_backend() -> ConcreteBackend {
+        ConcreteBackend::from_name(available_backends()[0])
+            .expect("ice: available backends can always be created")
+    }
+
+    /// Returns the name which selects this backend
+    pub fn name(&self) -> &'static str {
+        match self {
+            #[cfg(feature = "plonk_bn254")]
+            ConcreteBackend::Plonk(_) => "plonk",
+            #[cfg(feature = "marlin")]
+            ConcreteBackend::Marlin(_) => "marlin",
+            #[cfg(feature = "mock")]
+            ConcreteBackend::Mock(_) => "mock",
+        }
+    }
+
+    pub fn solve(
+        &self,
+        initial_witness: &mut BTreeMap<Witness, FieldElement>,
+        gates: Vec<Gate>,
+    ) -> GateResolution {
+        dispatch!(self, backend => backend.solve(initial_witness, gates))
+    }
+}
+
+impl ProofSystemCompiler for ConcreteBackend {
+    fn np_language(&self) -> Language {
+        dispatch!(self, backend => backend.np_language())
+    }
+
+    fn prove_with_meta(
+        &self,
+        circuit: Circuit,
+        witness_values: BTreeMap<Witness, FieldElement>,
+    ) -> Vec<u8> {
+        dispatch!(self, backend => backend.prove_with_meta(circuit, witness_values))
+    }
+
+    fn verify_from_cs(
+        &self,
+        proof: &[u8],
+        public_input: Vec<FieldElement>,
+        circuit: Circuit,
+    ) -> bool {
+        dispatch!(self, backend => backend.verify_from_cs(proof, public_input, circuit))
+    }
+}
+
+impl SmartContract for ConcreteBackend {
+    fn eth_contract_from_cs(&self, circuit: Circuit) -> String {
+        dispatch!(self, backend => backend.eth_contract_from_cs(circuit))
+    }
+}
+
+/// Returns the names of the backends which were compiled into nargo, starting with the default
+pub fn available_backends() -> Vec<&'static str> {
+    let mut available = Vec::new();
+    if cfg!(feature = "plonk_bn254") {
+        available.push("plonk");
+    }
+    if cfg!(feature = "marlin") {
+        available.push("marlin");
+    }
+    if cfg!(feature = "mock") {
+        available.push("mock");
+    }
+    available
+}
+
+/// Chooses the backend for the package in `program_dir`.
+///
+/// A backend requested on the command line takes precedence over the `backend`
+/// in the package's Nargo.toml, and the default backend is used if neither is given.
+pub fn select_backend(
+    program_dir: &Path,
+    requested: Option<&str>,
+) -> Result<ConcreteBackend, CliError> {
+    if let Some(name) = requested {
+        return ConcreteBackend::from_name(name);
+    }
+
+    let cfg_path = crate::find_package_config(program_dir)?;
+    match crate::toml::parse(&cfg_path)?.package.backend {
+        Some(name) => ConcreteBackend::from_name(&name).map_err(|err| {
+            CliError::Generic(format!("{}\n Location: {}", err, cfg_path.display()))
+        }),
+        None => Ok(ConcreteBackend::default_backend()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{available_backends, select_backend, KNOWN_BACKENDS};
+
+    fn package(dir: &Path, backend: Option<&str>) {
+        let backend = backend.map_or_else(String::new, |name| format!("backend = {:?}\n", name));
+        let manifest = format!("[package]\nauthors = []\n{}\n[dependencies]\n", backend);
+        std::fs::write(dir.join("Nargo.toml"), manifest).unwrap();
+    }
+
+    fn selected(dir: &Path, requested: Option<&str>) -> Result<&'static str, String> {
+        select_backend(dir, requested).map(|backend| backend.name()).map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn selects_the_backend_from_the_manifest_or_the_command_line() {
+        let dir = tempdir::TempDir::new("backend").unwrap();
+        let available = available_backends();
+        let default = available[0];
+        let last = available[available.len() - 1];
+
+        package(dir.path(), None);
+        assert_eq!(selected(dir.path(), None), Ok(default));
+        assert_eq!(selected(dir.path(), Some(last)), Ok(last));
+
+        package(dir.path(), Some(last));
+        assert_eq!(selected(dir.path(), None), Ok(last));
+        assert_eq!(selected(dir.path(), Some(default)), Ok(default));
+
+        // The command line takes precedence over a manifest naming an unknown backend
+        package(dir.path(), Some("groth16"));
+        assert_eq!(selected(dir.path(), Some(default)), Ok(default));
+        let err = selected(dir.path(), None).unwrap_err();
+        assert!(err.starts_with("unknown backend `groth16`"), "{}", err);
+        assert!(err.contains("Location"), "{}", err);
+    }
+
+    #[test]
+    fn rejects_unknown_and_unavailable_backends() {
+        let dir = tempdir::TempDir::new("backend").unwrap();
+        package(dir.path(), None);
+
+        let err = selected(dir.path(), Some("groth16")).unwrap_err();
+        assert!(err.starts_with("unknown backend `groth16`"), "{}", err);
+
+        // Backends of another field are never built in, so at least one known backend is unavailable
+        let (name, feature, field) =
+            KNOWN_BACKENDS.iter().find(|(name, ..)| !available_backends().contains(name)).unwrap();
+        let err = selected(dir.path(), Some(*name)).unwrap_err();
+        assert!(err.contains(&format!("without the `{}` feature", feature)), "{}", err);
+        assert!(err.contains(*field), "{}", err);
     }
 }
-// XXX: This works  because there are only two features, we want to say only one of these can be enabled. (feature xor)
-#[cfg(all(feature = "plonk", feature = "marlin"))]
-compile_error!("feature \"plonk\"  and feature \"marlin\" cannot be enabled at the same time");
@@ -0,0 +1,185 @@
+use serde_derive::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+use acvm::{hash_constraint_system, Language, ProofSystemCompiler};
+use noirc_driver::{CompiledProgram, Driver};
+
+use crate::{backends::ConcreteBackend, errors::CliError, workspace::dependency_root};
+
+/// The directory which nargo keeps its caches in, in the root of a package or workspace
+pub(crate) const TARGET_DIR: &str = "target";
+const CACHE_DIR: &str = "cache";
+const ENTRY_FILE: &str = "program.json";
+
+/// The version of the cache entries, this must be bumped whenever their representation changes
+const CACHE_VERSION: u32 = 3;
+
+/// A compiled program, along with the key of the inputs it was compiled from
+#[derive(Serialize, Deserialize)]
+struct CacheEntry<P> {
+    version: u32,
+    key: String,
+    // Detects entries which were modified after they were written
+    circuit_hash: String,
+    program: P,
+}
+
+/// Computes the key of compiling the crate built by `driver` with `backend`.
+///
+/// The key covers every file the compiler loaded, including the standard library and
+/// the dependencies, along with the version and sources of the compiler and what it compiles to,
+/// and which optional optimisations are applied to the circuit.
+pub(crate) fn cache_key(driver: &Driver, backend: &ConcreteBackend) -> String {
+    let language = match backend.np_language() {
+        Language::R1CS => "R1CS".to_owned(),
+        Language::PLONKCSat { width } => format!("PLONKCSat{}", width),
+    };
+    let compaction = if driver.compacts_witnesses() { "compact" } else { "" };
+    let range_optimisation = if driver.optimises_ranges() { "" } else { "no-range-optimisation" };
+
+    let mut hasher = Sha256::new();
+    for part in &[
+        env!("CARGO_PKG_VERSION"),
+        env!("NARGO_COMPILER_HASH"),
+        backend.name(),
+        language.as_str(),
+        compaction,
+        range_optimisation,
+    ] {
+        hasher.update(part.as_bytes());
+        hasher.update(&[0]);
+    }
+    for (path, source) in driver.loaded_files() {
+        hasher.update(path.to_string_lossy().as_bytes());
+        hasher.update(&[0]);
+        hasher.update(&(source.len() as u64).to_be_bytes());
+        hasher.update(source.as_bytes());
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Keeps the last compiled program of a package, so that it is not compiled again
+/// by the next command when nothing changed
+pub(crate) struct BuildCache {
+    entry_path: PathBuf,
+}
+
+impl BuildCache {
+    /// Returns the cache of the package in `program_dir`, which is kept in the
+    /// target directory of the package, or of its workspace.
+    ///
+    /// The entries of the members of a workspace are kept under their paths relative to its root,
+    /// as members in different directories can have the same name.
+    pub(crate) fn for_package(program_dir: &Path) -> Result<BuildCache, CliError> {
+        let (root_dir, member_path, _) = dependency_root(program_dir)?;
+        let entry_path =
+            root_dir.join(TARGET_DIR).join(CACHE_DIR).join(member_path).join(ENTRY_FILE);
+        Ok(BuildCache { entry_path })
+    }
+
+    /// Returns the cached program if it was compiled from the inputs with the given key
+    pub(crate) fn load(&self, key: &str) -> Option<CompiledProgram> {
+        let json = std::fs::read_to_string(&self.entry_path).ok()?;
+        let entry: CacheEntry<CompiledProgram> = serde_json::from_str(&json).ok()?;
+        let circuit_hash = hex::encode(hash_constraint_system(&entry.program.circuit));
+        if entry.version != CACHE_VERSION || entry.key != key || entry.circuit_hash != circuit_hash
+        {
+            return None;
+        }
+        Some(entry.program)
+    }
+
+    /// Replaces the cached program.
+    ///
+    /// The cache only saves time, so a program which cannot be cached is not an error.
+    pub(crate) fn store(&self, key: &str, program: &CompiledProgram) {
+        let entry = CacheEntry {
+            version: CACHE_VERSION,
+            key: key.to_owned(),
+            circuit_hash: hex::encode(hash_constraint_system(&program.circuit)),
+            program,
+        };
+        let json = serde_json::to_string(&entry).expect("ice: could not serialize the cache entry");
+        if let Some(cache_dir) = self.entry_path.parent() {
+            let _ = std::fs::create_dir_all(cache_dir);
+        }
+        let _ = std::fs::write(&self.entry_path, json);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::acir::circuit::{Circuit, PublicInputs};
+    use acvm::acir::native_types::Witness;
+    use noirc_driver::CompiledProgram;
+
+    use super::BuildCache;
+
+    fn program(num_witnesses: u32) -> CompiledProgram {
+        let circuit = Circuit {
+            current_witness_index: num_witnesses,
+            gates: Vec::new(),
+            public_inputs: PublicInputs(vec![Witness(1)]),
+        };
+        CompiledProgram { circuit, abi: None, removed_range_gates: 0 }
+    }
+
+    #[test]
+    fn reuses_programs_with_the_same_key() {
+        let dir = tempdir::TempDir::new("build_cache").unwrap();
+        let cache = BuildCache { entry_path: dir.path().join("target/cache/package.json") };
+        assert!(cache.load("abc").is_none());
+
+        cache.store("abc", &program(3));
+        assert_eq!(cache.load("abc").unwrap().circuit, program(3).circuit);
+        assert!(cache.load("def").is_none());
+
+        // A new program replaces the previous one
+        cache.store("def", &program(4));
+        assert!(cache.load("abc").is_none());
+        assert_eq!(cache.load("def").unwrap().circuit, program(4).circuit);
+    }
+
+    #[test]
+    fn members_with_the_same_name_have_their_own_entries() {
+        let dir = tempdir::TempDir::new("build_cache").unwrap();
+        std::fs::write(
+            dir.path().join("Nargo.toml"),
+            "[workspace]\nmembers = [\"circuits/main\", \"examples/main\"]\n",
+        )
+        .unwrap();
+        for member in &["circuits/main", "examples/main"] {
+            let package_dir = dir.path().join(member);
+            std::fs::create_dir_all(package_dir.join("src")).unwrap();
+            std::fs::write(
+                package_dir.join("Nargo.toml"),
+                "[package]\nauthors = []\n\n[dependencies]\n",
+            )
+            .unwrap();
+            std::fs::write(package_dir.join("src/main.nr"), "fn main(x : Field) {}").unwrap();
+        }
+
+        let circuits = BuildCache::for_package(&dir.path().join("circuits/main")).unwrap();
+        let examples = BuildCache::for_package(&dir.path().join("examples/main")).unwrap();
+        assert_ne!(circuits.entry_path, examples.entry_path);
+
+        circuits.store("abc", &program(3));
+        examples.store("abc", &program(4));
+        assert_eq!(circuits.load("abc").unwrap().circuit, program(3).circuit);
+        assert_eq!(examples.load("abc").unwrap().circuit, program(4).circuit);
+    }
+
+    #[test]
+    fn ignores_modified_entries() {
+        let dir = tempdir::TempDir::new("build_cache").unwrap();
+        let cache = BuildCache { entry_path: dir.path().join("package.json") };
+        cache.store("abc", &program(3));
+
+        let json = std::fs::read_to_string(&cache.entry_path).unwrap();
+        let modified = json.replace("\"current_witness_index\":3", "\"current_witness_index\":5");
+        assert_ne!(json, modified);
+        std::fs::write(&cache.entry_path, modified).unwrap();
+        assert!(cache.load("abc").is_none());
+    }
+}
@@ -0,0 +1,41 @@
+use clap::ArgMatches;
+use noirc_abi::Abi;
+use std::path::Path;
+
+use crate::{errors::CliError, resolver::Resolver};
+
+use super::{add_std_lib, check_crate, for_each_binary_package};
+
+pub(crate) fn run(args: ArgMatches) -> Result<(), CliError> {
+    let args = args.subcommand_matches("abi").unwrap();
+    for_each_binary_package(args, |package| {
+        let abi = abi_from_path(&package.dir)?;
+
+        let json = serde_json::to_string_pretty(&abi).expect("ice: could not serialize the ABI");
+        println!("{}", json);
+        Ok(())
+    })
+}
+
+/// Computes the ABI of the binary crate in the given package, without compiling it to ACIR
+pub fn abi_from_path<P: AsRef<Path>>(p: P) -> Result<Abi, CliError> {
+    let mut driver = Resolver::resolve_root_config(p.as_ref())?;
+    add_std_lib(&mut driver);
+    check_crate(&mut driver)?;
+
+    driver.compute_abi().ok_or_else(|| CliError::Generic("libraries do not have an ABI".to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn abi_round_trip() {
+        let mut program_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        program_dir.push("tests/test_data/struct_inputs");
+
+        let abi = super::abi_from_path(&program_dir).unwrap();
+        let json = serde_json::to_string(&abi).unwrap();
+        let deserialized: noirc_abi::Abi = serde_json::from_str(&json).unwrap();
+        assert_eq!(abi, deserialized);
+    }
+}
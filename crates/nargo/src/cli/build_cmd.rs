@@ -1,41 +1,50 @@
 use clap::ArgMatches;
+use noirc_abi::input_parser::Format;
 use std::path::{Path, PathBuf};
 
 use crate::{errors::CliError, resolver::Resolver};
 
-use super::{add_std_lib, write_to_file, PROVER_INPUT_FILE, VERIFIER_INPUT_FILE};
+use super::{
+    add_std_lib, check_crate, for_each_package, requested_input_format, resolve_input_format,
+    write_to_file, PROVER_INPUT_FILE, VERIFIER_INPUT_FILE,
+};
 
-pub(crate) fn run(_args: ArgMatches) -> Result<(), CliError> {
-    let package_dir = std::env::current_dir().unwrap();
-    build_from_path(package_dir)?;
-    println!("Constraint system successfully built!");
-    Ok(())
+pub(crate) fn run(args: ArgMatches) -> Result<(), CliError> {
+    let args = args.subcommand_matches("build").unwrap();
+    let input_format = requested_input_format(args);
+
+    for_each_package(args, |package| {
+        build_from_path(&package.dir, input_format)?;
+        println!("Constraint system successfully built!");
+        Ok(())
+    })
 }
 // This is exposed so that we can run the examples and verify that they pass
-pub fn build_from_path<P: AsRef<Path>>(p: P) -> Result<(), CliError> {
+pub fn build_from_path<P: AsRef<Path>>(p: P, input_format: Option<Format>) -> Result<(), CliError> {
     let mut driver = Resolver::resolve_root_config(p.as_ref())?;
     add_std_lib(&mut driver);
-    driver.build();
-    // XXX: We can have a --overwrite flag to determine if you want to overwrite the Prover/Verifier.toml files
+    check_crate(&mut driver)?;
+    // XXX: We can have a --overwrite flag to determine if you want to overwrite the Prover/Verifier input files
     if let Some(x) = driver.compute_abi() {
-        // XXX: The root config should return an enum to determine if we are looking for .json or .toml
-        // For now it is hard-coded to be toml.
-        //
-        // Check for input.toml and verifier.toml
+        // The input files are written in the same format as an existing prover input file,
+        // unless a format was requested explicitly
+        let input_format = resolve_input_format(&p, PROVER_INPUT_FILE, input_format)?;
+
         let path_to_root = PathBuf::from(p.as_ref());
-        let path_to_prover_input = path_to_root.join(format!("{}.toml", PROVER_INPUT_FILE));
-        let path_to_verifier_input = path_to_root.join(format!("{}.toml", VERIFIER_INPUT_FILE));
+        let path_to_prover_input =
+            path_to_root.join(PROVER_INPUT_FILE).with_extension(input_format.ext());
+        let path_to_verifier_input =
+            path_to_root.join(VERIFIER_INPUT_FILE).with_extension(input_format.ext());
 
         // If they are not available, then create them and
         // populate them based on the ABI
         if !path_to_prover_input.exists() {
-            let toml = toml::to_string(&x).unwrap();
-            write_to_file(toml.as_bytes(), &path_to_prover_input);
+            let template = input_template(&x, input_format);
+            write_to_file(template.as_bytes(), &path_to_prover_input);
         }
         if !path_to_verifier_input.exists() {
-            let abi = x.public_abi();
-            let toml = toml::to_string(&abi).unwrap();
-            write_to_file(toml.as_bytes(), &path_to_verifier_input);
+            let template = input_template(&x.public_abi(), input_format);
+            write_to_file(template.as_bytes(), &path_to_verifier_input);
         }
     } else {
         // This means that this is a library. Libraries do not have ABIs.
@@ -43,6 +52,18 @@ pub fn build_from_path<P: AsRef<Path>>(p: P) -> Result<(), CliError> {
     Ok(())
 }
 
+/// Returns an input file with an empty entry for each parameter in the ABI
+fn input_template(abi: &noirc_abi::Abi, input_format: Format) -> String {
+    let template = abi.input_template();
+    match input_format {
+        // Tables must come after all other values in TOML, which `toml::Value` takes care of
+        Format::Toml => {
+            toml::Value::try_from(template).and_then(|value| toml::to_string(&value)).unwrap()
+        }
+        Format::Json => serde_json::to_string_pretty(&template).unwrap(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     const TEST_DATA_DIR: &str = "tests/build_tests_data";
@@ -55,12 +76,11 @@ mod tests {
         let paths = std::fs::read_dir(pass_dir).unwrap();
         for path in paths.flatten() {
             let path = path.path();
-            assert!(super::build_from_path(path.clone()).is_ok(), "path: {}", path.display());
+            assert!(super::build_from_path(path.clone(), None).is_ok(), "path: {}", path.display());
         }
     }
 
     #[test]
-    #[ignore = "This test fails because the reporter exits the process with 1"]
     fn fail() {
         let mut fail_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         fail_dir.push(&format!("{TEST_DATA_DIR}/fail"));
@@ -68,7 +88,11 @@ mod tests {
         let paths = std::fs::read_dir(fail_dir).unwrap();
         for path in paths.flatten() {
             let path = path.path();
-            assert!(super::build_from_path(path.clone()).is_err(), "path: {}", path.display());
+            assert!(
+                super::build_from_path(path.clone(), None).is_err(),
+                "path: {}",
+                path.display()
+            );
         }
     }
 }
@@ -0,0 +1,71 @@
+use std::path::Path;
+
+use acvm::acir::circuit::Circuit;
+use clap::ArgMatches;
+
+use crate::{artifact::ProgramArtifact, errors::CliError};
+
+pub(crate) fn run(args: ArgMatches) -> Result<(), CliError> {
+    let args = args.subcommand_matches("check-acir").unwrap();
+    let path = Path::new(args.value_of("file").unwrap());
+    let circuit = read_circuit(path)?;
+
+    let violations = circuit.validate();
+    if violations.is_empty() {
+        println!("{} is well formed", path.display());
+        return Ok(());
+    }
+    let violations: Vec<_> = violations.iter().map(|violation| violation.to_string()).collect();
+    Err(CliError::Generic(format!(
+        "{} is malformed:\n    {}",
+        path.display(),
+        violations.join("\n    ")
+    )))
+}
+
+/// Reads a circuit in any of the forms `nargo compile` writes it in, which is chosen by the extension of the file
+fn read_circuit(path: &Path) -> Result<Circuit, CliError> {
+    let file_name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    if file_name.ends_with(&format!(".{}", super::ACIR_TEXT_EXT)) {
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| CliError::Generic(format!("cannot read {}: {}", path.display(), err)))?;
+        return Circuit::from_text(&text)
+            .map_err(|err| CliError::Generic(format!("{}:{}", path.display(), err)));
+    }
+    if path.extension().map_or(false, |ext| ext == super::ARTIFACT_EXT) {
+        return Ok(ProgramArtifact::read(path)?.circuit);
+    }
+
+    let bytes = std::fs::read(path)
+        .map_err(|err| CliError::Generic(format!("cannot read {}: {}", path.display(), err)))?;
+    Circuit::from_bytes(&bytes).map_err(|err| {
+        CliError::Generic(format!("{} is not a valid circuit: {}", path.display(), err))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::acir::circuit::Circuit;
+
+    use super::read_circuit;
+
+    #[test]
+    fn reads_every_circuit_format() {
+        let dir = tempdir::TempDir::new("check-acir").unwrap();
+        let circuit = Circuit::from_text(
+            "current_witness_index 3\npublic_inputs [x1]\narithmetic 1*x1*x2 + -1*x3 + 0 = 0\n",
+        )
+        .unwrap();
+
+        let text_path = dir.path().join("main.acir.txt");
+        std::fs::write(&text_path, circuit.to_text()).unwrap();
+        assert_eq!(read_circuit(&text_path).unwrap(), circuit);
+
+        let binary_path = dir.path().join("main.acir");
+        std::fs::write(&binary_path, circuit.to_bytes()).unwrap();
+        assert_eq!(read_circuit(&binary_path).unwrap(), circuit);
+
+        std::fs::write(&binary_path, circuit.to_text()).unwrap();
+        assert!(read_circuit(&binary_path).is_err());
+    }
+}
@@ -7,34 +7,51 @@ use clap::ArgMatches;
 
 use std::path::Path;
 
-use crate::{errors::CliError, resolver::Resolver};
+use crate::{
+    artifact::ProgramArtifact,
+    backends::{select_backend, ConcreteBackend},
+    build_cache::{cache_key, BuildCache},
+    errors::CliError,
+    resolver::Resolver,
+};
 
-use super::{add_std_lib, create_named_dir, write_to_file, BUILD_DIR};
+use super::{
+    add_std_lib, check_crate, configure_driver, create_named_dir, for_each_binary_package,
+    report_errors, requested_input_options, write_to_file, InputOptions, BUILD_DIR,
+};
 
 pub(crate) fn run(args: ArgMatches) -> Result<(), CliError> {
     let args = args.subcommand_matches("compile").unwrap();
     let circuit_name = args.value_of("circuit_name").unwrap();
     let witness = args.is_present("witness");
+    let emit_text = args.value_of("emit") == Some("acir-text");
+    let input_options = requested_input_options(args);
 
-    let curr_dir = std::env::current_dir().unwrap();
-    let mut circuit_path = PathBuf::new();
-    circuit_path.push(BUILD_DIR);
-
-    let result =
-        generate_circuit_and_witness_to_disk(circuit_name, curr_dir, circuit_path, witness);
-    match result {
-        Ok(_) => Ok(()),
-        Err(e) => Err(e),
-    }
+    for_each_binary_package(args, |package| {
+        let backend = select_backend(&package.dir, args.value_of("backend"))?;
+        generate_circuit_and_witness_to_disk(
+            &backend,
+            circuit_name,
+            package.dir.clone(),
+            package.output_dir(BUILD_DIR),
+            witness,
+            emit_text,
+            input_options,
+        )?;
+        Ok(())
+    })
 }
 
 pub fn generate_circuit_and_witness_to_disk<P: AsRef<Path>>(
+    backend: &ConcreteBackend,
     circuit_name: &str,
     program_dir: P,
     circuit_dir: P,
     generate_witness: bool,
+    emit_text: bool,
+    input_options: InputOptions,
 ) -> Result<PathBuf, CliError> {
-    let compiled_program = compile_circuit(program_dir.as_ref(), false)?;
+    let compiled_program = compile_circuit(backend, program_dir.as_ref(), false)?;
     let serialized = compiled_program.circuit.to_bytes();
 
     let mut circuit_path = create_named_dir(circuit_dir.as_ref(), "build");
@@ -44,8 +61,26 @@ pub fn generate_circuit_and_witness_to_disk<P: AsRef<Path>>(
     println!("Generated ACIR code into {}", path);
     println!("{:?}", std::fs::canonicalize(&circuit_path));
 
+    // The artifact contains everything needed to verify a proof without the source code
+    let abi = compiled_program.abi.clone().expect("ice: binary crates always have an ABI");
+    let artifact = ProgramArtifact::new(backend.name(), compiled_program.circuit.clone(), abi);
+    let artifact_path = circuit_path.with_extension(crate::cli::ARTIFACT_EXT);
+    let path = write_to_file(artifact.to_json().as_bytes(), &artifact_path);
+    println!("Generated build artifact into {}", path);
+
+    if emit_text {
+        let text_path = circuit_path.with_extension(crate::cli::ACIR_TEXT_EXT);
+        let path = write_to_file(compiled_program.circuit.to_text().as_bytes(), &text_path);
+        println!("Generated ACIR text into {}", path);
+    }
+
     if generate_witness {
-        let solved_witness = super::prove_cmd::solve_witness(program_dir, &compiled_program)?;
+        let solved_witness = super::prove_cmd::solve_witness(
+            backend,
+            program_dir,
+            &compiled_program,
+            input_options,
+        )?;
         let buf = Witness::to_bytes(&solved_witness);
 
         circuit_path.pop();
@@ -58,13 +93,27 @@ pub fn generate_circuit_and_witness_to_disk<P: AsRef<Path>>(
 }
 
 pub fn compile_circuit<P: AsRef<Path>>(
+    backend: &ConcreteBackend,
     program_dir: P,
     show_ssa: bool,
 ) -> Result<noirc_driver::CompiledProgram, CliError> {
     let mut driver = Resolver::resolve_root_config(program_dir.as_ref())?;
-    let backend = crate::backends::ConcreteBackend;
     add_std_lib(&mut driver);
-    let compiled_program = driver.into_compiled_program(backend.np_language(), show_ssa);
+    configure_driver(&mut driver);
+    check_crate(&mut driver)?;
+
+    // The program is compiled again when its SSA is requested, as it is only printed while compiling
+    let cache = BuildCache::for_package(program_dir.as_ref())?;
+    let key = cache_key(&driver, backend);
+    if !show_ssa {
+        if let Some(compiled_program) = cache.load(&key) {
+            return Ok(compiled_program);
+        }
+    }
 
+    let compiled_program = driver
+        .compile_main(backend.np_language(), show_ssa)
+        .map_err(|diagnostics| report_errors(&driver, diagnostics))?;
+    cache.store(&key, &compiled_program);
     Ok(compiled_program)
 }
@@ -0,0 +1,173 @@
+use std::path::{Path, PathBuf};
+
+use acvm::acir::native_types::Witness;
+use acvm::FieldElement;
+use clap::ArgMatches;
+use noirc_abi::input_parser::InputValue;
+use noirc_abi::{AbiType, Sign};
+
+use super::{
+    compile_cmd::compile_circuit, create_named_dir, for_each_binary_package,
+    requested_input_options, write_to_file, InputOptions, BUILD_DIR,
+};
+use crate::{
+    backends::{select_backend, ConcreteBackend},
+    errors::CliError,
+};
+
+pub(crate) fn run(args: ArgMatches) -> Result<(), CliError> {
+    let args = args.subcommand_matches("execute").unwrap();
+    let show_ssa = args.is_present("show-ssa");
+    let input_options = requested_input_options(args);
+
+    for_each_binary_package(args, |package| {
+        let backend = select_backend(&package.dir, args.value_of("backend"))?;
+        // Default to naming the witness after the package directory
+        let witness_name = args.value_of("witness-name").unwrap_or(&package.name);
+
+        let (return_value, witness_path) = execute_with_path(
+            &backend,
+            witness_name,
+            package.dir.clone(),
+            package.output_dir(BUILD_DIR),
+            show_ssa,
+            input_options,
+        )?;
+
+        println!("Circuit witness successfully solved");
+        if let Some((return_value, return_type)) = return_value {
+            println!("Circuit output: {}", format_input_value(&return_value, &return_type));
+        }
+        println!("Witness saved to {}", witness_path.display());
+        Ok(())
+    })
+}
+
+/// Solves the witness of the program in `program_dir` without creating a proof,
+/// then writes it to `witness_dir`. The verifier input file is left untouched.
+///
+/// Returns the value returned by `main` along with its type, if `main` returns a value.
+pub fn execute_with_path<P: AsRef<Path>>(
+    backend: &ConcreteBackend,
+    witness_name: &str,
+    program_dir: P,
+    witness_dir: P,
+    show_ssa: bool,
+    input_options: InputOptions,
+) -> Result<(Option<(InputValue, AbiType)>, PathBuf), CliError> {
+    let compiled_program = compile_circuit(backend, program_dir.as_ref(), show_ssa)?;
+    let (solved_witness, return_value) = super::prove_cmd::solve_witness_and_return_value(
+        backend,
+        &program_dir,
+        &compiled_program,
+        input_options,
+    )?;
+
+    let return_type = compiled_program
+        .abi
+        .unwrap()
+        .parameters
+        .into_iter()
+        .find(|(param_name, _)| param_name == noirc_frontend::hir_def::function::MAIN_RETURN_NAME)
+        .map(|(_, param_type)| param_type);
+
+    let mut witness_path = create_named_dir(witness_dir.as_ref(), "build");
+    witness_path.push(witness_name);
+    witness_path.set_extension(crate::cli::WITNESS_EXT);
+    write_to_file(&Witness::to_bytes(&solved_witness), &witness_path);
+
+    Ok((return_value.zip(return_type), witness_path))
+}
+
+/// Formats an input value according to its ABI type.
+/// Fields are displayed in hexadecimal and integers in decimal.
+fn format_input_value(value: &InputValue, typ: &AbiType) -> String {
+    match (value, typ) {
+        (InputValue::Field(element), _) => format_field_element(element, typ),
+        (InputValue::Vec(elements), AbiType::Array { typ, .. }) => {
+            let elements: Vec<_> =
+                elements.iter().map(|element| format_input_value(element, typ)).collect();
+            format!("[{}]", elements.join(", "))
+        }
+        (InputValue::Vec(elements), AbiType::Tuple { fields, .. }) => {
+            let elements: Vec<_> = elements
+                .iter()
+                .zip(fields)
+                .map(|(element, typ)| format_input_value(element, typ))
+                .collect();
+            format!("({})", elements.join(", "))
+        }
+        (InputValue::Struct(values), AbiType::Struct { fields, .. }) => {
+            let fields: Vec<_> = fields
+                .iter()
+                .map(|(name, typ)| format!("{}: {}", name, format_input_value(&values[name], typ)))
+                .collect();
+            format!("{{ {} }}", fields.join(", "))
+        }
+        (InputValue::Undefined, _) => "undefined".to_owned(),
+        // The return value has already been checked against its ABI type
+        (InputValue::Vec(_) | InputValue::Struct(_), _) => unreachable!(),
+    }
+}
+
+fn format_field_element(element: &FieldElement, typ: &AbiType) -> String {
+    match typ {
+        AbiType::Integer { sign: Sign::Unsigned, .. } => element.to_u128().to_string(),
+        AbiType::Integer { sign: Sign::Signed, width, .. } => {
+            // Signed integers are stored in two's complement
+            let value = element.to_u128();
+            if *width < 128 && value >= 1 << (width - 1) {
+                (value as i128 - (1 << width)).to_string()
+            } else {
+                (value as i128).to_string()
+            }
+        }
+        AbiType::Boolean(_) => (!element.is_zero()).to_string(),
+        _ => format!("0x{}", element.to_hex()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use acvm::acir::native_types::Witness;
+    use acvm::FieldElement;
+
+    use super::execute_with_path;
+    use crate::{backends::select_backend, cli::InputOptions};
+
+    #[test]
+    fn solves_the_witness_without_writing_verifier_inputs() {
+        // The fixture is copied so that running it cannot leave a verifier input file behind
+        let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/test_data/main_return");
+        let dir = tempdir::TempDir::new("execute").unwrap();
+        let program_dir = dir.path().join("main_return");
+        std::fs::create_dir_all(program_dir.join("src")).unwrap();
+        for file in ["Nargo.toml", "Prover.toml", "src/main.nr"] {
+            std::fs::copy(fixture.join(file), program_dir.join(file)).unwrap();
+        }
+
+        let backend = select_backend(&program_dir, None).unwrap();
+        let (return_value, witness_path) = execute_with_path(
+            &backend,
+            "main_return",
+            program_dir.clone(),
+            dir.path().join("build"),
+            false,
+            InputOptions::default(),
+        )
+        .unwrap();
+
+        let (return_value, _) = return_value.expect("main returns a value");
+        assert_eq!(return_value.to_fields(), vec![FieldElement::from(8_u128)]);
+
+        assert_eq!(witness_path, dir.path().join("build/main_return.tr"));
+        let witness = Witness::from_bytes(&std::fs::read(&witness_path).unwrap());
+        // `x` is the first witness and the value returned by main is the second
+        assert_eq!(witness[&Witness(1)], FieldElement::from(8_u128));
+        assert_eq!(witness[&Witness(2)], FieldElement::from(8_u128));
+
+        assert!(!program_dir.join("Verifier.toml").exists());
+    }
+}
@@ -3,32 +3,40 @@ use std::collections::HashMap;
 use clap::ArgMatches;
 use std::path::Path;
 
+use crate::backends::{select_backend, ConcreteBackend};
 use crate::cli::compile_cmd::compile_circuit;
+use crate::cli::for_each_binary_package;
 use crate::errors::CliError;
 
+/// The name of the range gates, which are the only gates that the range optimiser removes
+const RANGE_GATE: &str = "range";
+
 pub(crate) fn run(args: ArgMatches) -> Result<(), CliError> {
     let args = args.subcommand_matches("gates").unwrap();
     let show_ssa = args.is_present("show-ssa");
-    count_gates(show_ssa)
-}
-
-pub fn count_gates(show_ssa: bool) -> Result<(), CliError> {
-    let curr_dir = std::env::current_dir().unwrap();
-    count_gates_with_path(curr_dir, show_ssa)
+    for_each_binary_package(args, |package| {
+        let backend = select_backend(&package.dir, args.value_of("backend"))?;
+        count_gates_with_path(&backend, &package.dir, show_ssa)
+    })
 }
 
 pub fn count_gates_with_path<P: AsRef<Path>>(
+    backend: &ConcreteBackend,
     program_dir: P,
     show_ssa: bool,
 ) -> Result<(), CliError> {
-    let compiled_program = compile_circuit(program_dir.as_ref(), show_ssa)?;
+    let compiled_program = compile_circuit(backend, program_dir.as_ref(), show_ssa)?;
     let gates = compiled_program.circuit.gates;
+    let removed_range_gates = compiled_program.removed_range_gates as u32;
 
     // Store counts of each gate type into hashmap.
     let mut gate_counts: HashMap<&str, u32> = HashMap::new();
     for gate in gates.iter() {
         *gate_counts.entry(gate.name()).or_default() += 1;
     }
+    if removed_range_gates > 0 {
+        gate_counts.entry(RANGE_GATE).or_default();
+    }
 
     // Sort gates by name alphabetically for consistent display.
     let mut sorted_gate_counts: Vec<(&str, u32)> = gate_counts.into_iter().collect();
@@ -36,13 +44,27 @@ pub fn count_gates_with_path<P: AsRef<Path>>(
 
     println!("Gates successfully counted\n");
 
-    println!("Total gates: {}\n", gates.len());
+    println!(
+        "Total gates: {}{}\n",
+        gates.len(),
+        before_range_optimisation(gates.len() as u32, removed_range_gates)
+    );
 
     println!("By type:");
 
     for (gate_type, count) in sorted_gate_counts {
-        println!("{}: {}", gate_type, count);
+        let removed = if gate_type == RANGE_GATE { removed_range_gates } else { 0 };
+        println!("{}: {}{}", gate_type, count, before_range_optimisation(count, removed));
     }
 
     Ok(())
 }
+
+/// Describes how many gates there were before the range optimiser removed some, if it did
+fn before_range_optimisation(count: u32, removed: u32) -> String {
+    if removed == 0 {
+        String::new()
+    } else {
+        format!(" ({} before range optimisation)", count + removed)
+    }
+}
@@ -1,23 +1,32 @@
 pub use build_cmd::build_from_path;
-use clap::{App, Arg};
-use noirc_driver::Driver;
+use clap::{App, Arg, ArgMatches};
+use noirc_abi::input_parser::{FieldFormat, Format};
+use noirc_driver::{Driver, FileDiagnostic};
 use noirc_frontend::graph::{CrateName, CrateType};
 use std::{
     fs::File,
     io::Write,
     path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
 };
 extern crate tempdir;
 use tempdir::TempDir;
 
 use crate::errors::CliError;
+use crate::workspace::{select_packages, SelectedPackage};
 
+mod abi_cmd;
 mod build_cmd;
+mod check_acir_cmd;
 mod compile_cmd;
 mod contract_cmd;
+mod execute_cmd;
 mod gates_cmd;
 mod new_cmd;
 mod prove_cmd;
+mod test_cmd;
+mod update_cmd;
+mod vendor_cmd;
 mod verify_cmd;
 
 const CONTRACT_DIR: &str = "contract";
@@ -25,19 +34,99 @@ const PROOFS_DIR: &str = "proofs";
 const PROVER_INPUT_FILE: &str = "Prover";
 const VERIFIER_INPUT_FILE: &str = "Verifier";
 const SRC_DIR: &str = "src";
-const PKG_FILE: &str = "Nargo.toml";
+pub(crate) const PKG_FILE: &str = "Nargo.toml";
 const PROOF_EXT: &str = "proof";
 const BUILD_DIR: &str = "build";
 const ACIR_EXT: &str = "acir";
+const ACIR_TEXT_EXT: &str = "acir.txt";
+const ARTIFACT_EXT: &str = "json";
 const WITNESS_EXT: &str = "tr";
 
 pub fn start_cli() {
+    let input_format = Arg::with_name("input-format")
+        .long("input-format")
+        .takes_value(true)
+        .possible_values(&["toml", "json"])
+        .help("The format of the Prover and Verifier input files, detected from the files on disk if not given");
+    let field_format = Arg::with_name("field-format")
+        .long("field-format")
+        .takes_value(true)
+        .possible_values(&["hex", "decimal"])
+        .help("How field elements are written to the Verifier input file, defaults to hex");
+
     let matches = App::new("nargo")
         .about("Noir's package manager")
         .version("0.1")
         .author("Kevaundray Wedderburn <kevtheappdev@gmail.com>")
-        .subcommand(App::new("build").about("Builds the constraint system"))
+        .arg(
+            Arg::with_name("backend")
+                .long("backend")
+                .takes_value(true)
+                .global(true)
+                .help("The proving backend to use, overriding the backend in Nargo.toml. Only backends using the field nargo was built for are available: \
+                       a single nargo binary cannot choose between plonk (bn254) and marlin (bls12_381), marlin needs a nargo built with `--no-default-features --features marlin`"),
+        )
+        .arg(
+            Arg::with_name("offline")
+                .long("offline")
+                .global(true)
+                .help("Only use the git dependencies which were already downloaded into ~/nargo"),
+        )
+        .arg(
+            Arg::with_name("message-format")
+                .long("message-format")
+                .takes_value(true)
+                .global(true)
+                .possible_values(&["human", "json"])
+                .help("How compiler diagnostics are printed, json prints one object per line to stdout"),
+        )
+        .arg(
+            Arg::with_name("deny-warnings")
+                .long("deny-warnings")
+                .global(true)
+                .help("Fails the compilation of a package if it has any warnings"),
+        )
+        .arg(
+            Arg::with_name("compact-witnesses")
+                .long("compact-witnesses")
+                .global(true)
+                .help("Removes the gates which do not affect the circuit and renumbers its witnesses without gaps"),
+        )
+        .arg(
+            Arg::with_name("stdlib-path")
+                .long("stdlib-path")
+                .takes_value(true)
+                .global(true)
+                .help("Uses the standard library in the given directory instead of the one built into nargo"),
+        )
+        .arg(
+            Arg::with_name("package")
+                .long("package")
+                .short("p")
+                .takes_value(true)
+                .global(true)
+                .help("The member of the workspace to use, all members are used if not given in the root of the workspace"),
+        )
+        .subcommand(
+            App::new("build").about("Builds the constraint system").arg(input_format.clone()),
+        )
+        .subcommand(
+            App::new("abi").about("Prints the ABI of the program, with the types of its parameters, as JSON"),
+        )
         .subcommand(App::new("contract").about("Creates the smart contract code for circuit"))
+        .subcommand(
+            App::new("update")
+                .about("Fetches the git dependencies again and records their commits in Nargo.lock"),
+        )
+        .subcommand(
+            App::new("vendor")
+                .about("Copies all of the dependencies into the vendor directory, builds then use these copies")
+                .arg(
+                    Arg::with_name("check")
+                        .long("check")
+                        .help("Checks that the vendored dependencies match their sources, without copying them"),
+                ),
+        )
         .subcommand(
             App::new("new")
                 .about("Create a new binary project")
@@ -49,7 +138,26 @@ pub fn start_cli() {
         .subcommand(
             App::new("verify")
                 .about("Given a proof and a program, verify whether the proof is valid")
-                .arg(Arg::with_name("proof").help("The proof to verify").required(true)),
+                .arg(
+                    Arg::with_name("proof")
+                        .help("The name of the proof to verify")
+                        .required_unless("artifact"),
+                )
+                .arg(
+                    Arg::with_name("artifact")
+                        .long("artifact")
+                        .takes_value(true)
+                        .requires("proof-path")
+                        .help("Verify against a build artifact from `nargo compile` instead of compiling the program, with the backend it was compiled for"),
+                )
+                .arg(
+                    Arg::with_name("proof-path")
+                        .long("proof")
+                        .takes_value(true)
+                        .requires("artifact")
+                        .help("The path to the proof to verify against the artifact"),
+                )
+                .arg(input_format.clone()),
         )
         .subcommand(
             App::new("prove")
@@ -59,7 +167,9 @@ pub fn start_cli() {
                     Arg::with_name("show-ssa")
                         .long("show-ssa")
                         .help("Emit debug information for the intermediate SSA IR"),
-                ),
+                )
+                .arg(input_format.clone())
+                .arg(field_format.clone()),
         )
         .subcommand(
             App::new("compile")
@@ -71,6 +181,53 @@ pub fn start_cli() {
                     Arg::with_name("witness")
                         .long("witness")
                         .help("Solve the witness and write it to file along with the ACIR"),
+                )
+                .arg(
+                    Arg::with_name("emit")
+                        .long("emit")
+                        .takes_value(true)
+                        .possible_values(&["acir-text"])
+                        .help("Also write the ACIR in a human readable form, which can be read back losslessly"),
+                )
+                .arg(input_format.clone())
+                .arg(field_format),
+        )
+        .subcommand(
+            App::new("execute")
+                .about("Solve the witness of this program and print the value returned by main, without creating a proof")
+                .arg(
+                    Arg::with_name("witness-name")
+                        .long("witness-name")
+                        .takes_value(true)
+                        .help("The name of the witness file, defaults to the name of the package directory"),
+                )
+                .arg(
+                    Arg::with_name("show-ssa")
+                        .long("show-ssa")
+                        .help("Emit debug information for the intermediate SSA IR"),
+                )
+                .arg(input_format),
+        )
+        .subcommand(
+            App::new("test")
+                .about("Run the tests for this program")
+                .arg(
+                    Arg::with_name("test_name")
+                        .help("If given, only tests with names containing this string will be run"),
+                )
+                .arg(
+                    Arg::with_name("show-ssa")
+                        .long("show-ssa")
+                        .help("Emit debug information for the intermediate SSA IR"),
+                ),
+        )
+        .subcommand(
+            App::new("check-acir")
+                .about("Checks that a circuit is well formed, such as one produced by another tool")
+                .arg(
+                    Arg::with_name("file")
+                        .help("The circuit, either as ACIR, ACIR text or a build artifact")
+                        .required(true),
                 ),
         )
         .subcommand(
@@ -82,14 +239,31 @@ pub fn start_cli() {
         )
         .get_matches();
 
+    crate::git::set_offline(matches.is_present("offline"));
+    JSON_MESSAGES.store(matches.value_of("message-format") == Some("json"), Ordering::Relaxed);
+    DENY_WARNINGS.store(matches.is_present("deny-warnings"), Ordering::Relaxed);
+    COMPACT_WITNESSES.store(matches.is_present("compact-witnesses"), Ordering::Relaxed);
+
+    if let Some(stdlib_path) = matches.value_of("stdlib-path") {
+        if let Err(err) = crate::stdlib::set_stdlib_override(Path::new(stdlib_path)) {
+            err.write()
+        }
+    }
+
     let result = match matches.subcommand_name() {
         Some("new") => new_cmd::run(matches),
+        Some("abi") => abi_cmd::run(matches),
         Some("build") => build_cmd::run(matches),
         Some("contract") => contract_cmd::run(matches),
         Some("prove") => prove_cmd::run(matches),
         Some("compile") => compile_cmd::run(matches),
+        Some("execute") => execute_cmd::run(matches),
         Some("verify") => verify_cmd::run(matches),
         Some("gates") => gates_cmd::run(matches),
+        Some("check-acir") => check_acir_cmd::run(matches),
+        Some("test") => test_cmd::run(matches),
+        Some("update") => update_cmd::run(matches),
+        Some("vendor") => vendor_cmd::run(matches),
         None => Err(CliError::Generic("No subcommand was used".to_owned())),
         Some(x) => Err(CliError::Generic(format!("unknown command : {}", x))),
     };
@@ -98,6 +272,114 @@ pub fn start_cli() {
     }
 }
 
+/// Runs `command` on each package selected on the command line, naming the package
+/// when a workspace has more than one
+fn for_each_package<F>(args: &ArgMatches, command: F) -> Result<(), CliError>
+where
+    F: FnMut(&SelectedPackage) -> Result<(), CliError>,
+{
+    let current_dir = std::env::current_dir().unwrap();
+    let packages = select_packages(&current_dir, args.value_of("package"))?;
+    run_on_packages(&packages, false, command)
+}
+
+/// Runs `command`, which needs a binary crate, on each package selected on the command line.
+///
+/// When several packages are selected, the libraries among them are only checked for errors,
+/// as they cannot be compiled into a circuit. A library which is selected on its own is still
+/// passed to `command`, so that it reports why the library cannot be used.
+fn for_each_binary_package<F>(args: &ArgMatches, command: F) -> Result<(), CliError>
+where
+    F: FnMut(&SelectedPackage) -> Result<(), CliError>,
+{
+    let current_dir = std::env::current_dir().unwrap();
+    let packages = select_packages(&current_dir, args.value_of("package"))?;
+    run_on_packages(&packages, true, command)
+}
+
+fn run_on_packages<F>(
+    packages: &[SelectedPackage],
+    binary_only: bool,
+    mut command: F,
+) -> Result<(), CliError>
+where
+    F: FnMut(&SelectedPackage) -> Result<(), CliError>,
+{
+    for package in packages {
+        if packages.len() > 1 {
+            println!("[{}]", package.name);
+        }
+        if binary_only && packages.len() > 1 && is_library(&package.dir)? {
+            let mut driver = crate::resolver::Resolver::resolve_root_config(&package.dir)?;
+            add_std_lib(&mut driver);
+            check_crate(&mut driver)?;
+            println!("Library checked, libraries cannot be compiled into a circuit");
+            continue;
+        }
+        command(package)?;
+    }
+    Ok(())
+}
+
+fn is_library(package_dir: &Path) -> Result<bool, CliError> {
+    let (_, crate_type) = crate::lib_or_bin(package_dir)?;
+    Ok(crate_type == CrateType::Library)
+}
+
+/// Returns the input file format which was requested on the command line, if any
+fn requested_input_format(args: &ArgMatches) -> Option<Format> {
+    // clap only accepts the extensions of the supported formats
+    args.value_of("input-format").map(|ext| Format::from_ext(ext).unwrap())
+}
+
+/// Options for reading the Prover input file and writing the Verifier input file
+#[derive(Debug, Clone, Copy, Default)]
+pub struct InputOptions {
+    /// The format of the input files, detected from the files on disk if `None`
+    pub format: Option<Format>,
+    /// How field elements are written to the Verifier input file
+    pub field_format: FieldFormat,
+}
+
+/// Returns the input options which were requested on the command line
+fn requested_input_options(args: &ArgMatches) -> InputOptions {
+    let field_format = match args.value_of("field-format") {
+        Some("decimal") => FieldFormat::Decimal,
+        _ => FieldFormat::Hex,
+    };
+    InputOptions { format: requested_input_format(args), field_format }
+}
+
+/// Determines the format of the input file `file_name` in `dir`.
+///
+/// A format which was requested explicitly is always used. Otherwise the format
+/// is detected from the extension of the input file which exists on disk,
+/// falling back to TOML if there is none.
+fn resolve_input_format<P: AsRef<Path>>(
+    dir: P,
+    file_name: &str,
+    requested: Option<Format>,
+) -> Result<Format, CliError> {
+    if let Some(format) = requested {
+        return Ok(format);
+    }
+
+    let existing: Vec<_> = [Format::Toml, Format::Json]
+        .iter()
+        .copied()
+        .filter(|format| dir.as_ref().join(file_name).with_extension(format.ext()).exists())
+        .collect();
+
+    match existing.as_slice() {
+        [] => Ok(Format::Toml),
+        [format] => Ok(*format),
+        _ => Err(CliError::Generic(format!(
+            "found both {0}.toml and {0}.json, use --input-format to choose which one to use",
+            file_name
+        ))),
+    }
+}
+
 fn create_dir<P: AsRef<Path>>(dir_path: P) -> Result<PathBuf, std::io::Error> {
     let mut dir = std::path::PathBuf::new();
     dir.push(dir_path);
@@ -125,32 +407,90 @@ fn write_to_file(bytes: &[u8], path: &Path) -> String {
 
 // helper function which tests noir programs by trying to generate a proof and verify it
 pub fn prove_and_verify(proof_name: &str, prg_dir: &Path, show_ssa: bool) -> bool {
+    let backend = match crate::backends::select_backend(prg_dir, None) {
+        Ok(backend) => backend,
+        Err(err) => {
+            println!("Error: {}", err);
+            return false;
+        }
+    };
     let tmp_dir = TempDir::new("p_and_v_tests").unwrap();
-    let proof_path =
-        match prove_cmd::prove_with_path(proof_name, prg_dir, &tmp_dir.into_path(), show_ssa) {
-            Ok(p) => p,
-            Err(CliError::Generic(msg)) => {
-                println!("Error: {}", msg);
-                return false;
-            }
-            Err(CliError::DestinationAlreadyExists(str)) => {
-                println!("Error, destination {} already exists: ", str);
-                return false;
-            }
-        };
+    let proof_path = match prove_cmd::prove_with_path(
+        &backend,
+        proof_name,
+        prg_dir,
+        &tmp_dir.into_path(),
+        show_ssa,
+        InputOptions::default(),
+    ) {
+        Ok(p) => p,
+        Err(err) => {
+            println!("Error: {}", err);
+            return false;
+        }
+    };
 
-    verify_cmd::verify_with_path(prg_dir, &proof_path, show_ssa).unwrap()
+    verify_cmd::verify_with_path(&backend, prg_dir, &proof_path, show_ssa, None).unwrap()
 }
 
 fn add_std_lib(driver: &mut Driver) {
-    let path_to_std_lib_file = path_to_stdlib().join("lib.nr");
+    let path_to_std_lib_file = crate::stdlib::load_stdlib(driver);
     let std_crate = driver.create_non_local_crate(path_to_std_lib_file, CrateType::Library);
     let std_crate_name = "std";
     driver.propagate_dep(std_crate, &CrateName::new(std_crate_name).unwrap());
 }
 
-fn path_to_stdlib() -> PathBuf {
-    dirs::config_dir().unwrap().join("noir-lang").join("std/src")
+/// Whether diagnostics are printed as JSON rather than rendered for the terminal
+static JSON_MESSAGES: AtomicBool = AtomicBool::new(false);
+
+/// Whether warnings fail the compilation of a package, as errors do
+static DENY_WARNINGS: AtomicBool = AtomicBool::new(false);
+
+/// Whether compiled circuits are compacted, see `Driver::set_compact_witnesses`
+static COMPACT_WITNESSES: AtomicBool = AtomicBool::new(false);
+
+/// Applies the options given on the command line which change how programs are compiled
+fn configure_driver(driver: &mut Driver) {
+    driver.set_compact_witnesses(COMPACT_WITNESSES.load(Ordering::Relaxed));
+}
+
+/// Prints diagnostics in the format chosen with `--message-format`
+pub(crate) fn print_diagnostics(driver: &Driver, diagnostics: &[FileDiagnostic]) {
+    if JSON_MESSAGES.load(Ordering::Relaxed) {
+        for line in driver.diagnostics_to_json(diagnostics) {
+            println!("{}", line);
+        }
+    } else {
+        driver.report_errors(diagnostics);
+    }
+}
+
+/// Prints the diagnostics of a package which failed to compile, returning the error which aborts the command
+pub(crate) fn report_errors(driver: &Driver, diagnostics: Vec<FileDiagnostic>) -> CliError {
+    print_diagnostics(driver, &diagnostics);
+    let deny_warnings = DENY_WARNINGS.load(Ordering::Relaxed);
+    let error_count = diagnostics
+        .iter()
+        .filter(|diagnostic| deny_warnings || diagnostic.diagnostic.is_error())
+        .count();
+    CliError::CompilationFailed(error_count)
+}
+
+/// Statically analyses the local crate of the driver, printing any warnings.
+///
+/// This fails if the crate has errors, or if it has warnings and `--deny-warnings` was passed.
+pub(crate) fn check_crate(driver: &mut Driver) -> Result<(), CliError> {
+    match driver.build() {
+        Ok(warnings) if warnings.is_empty() => Ok(()),
+        Ok(warnings) if DENY_WARNINGS.load(Ordering::Relaxed) => {
+            Err(report_errors(driver, warnings))
+        }
+        Ok(warnings) => {
+            print_diagnostics(driver, &warnings);
+            Ok(())
+        }
+        Err(diagnostics) => Err(report_errors(driver, diagnostics)),
+    }
 }
 
 // FIXME: I not sure that this is the right place for this tests.
@@ -185,6 +525,72 @@ mod tests {
         }
     }
 
+    #[test]
+    fn input_format_is_detected_from_the_files_on_disk() {
+        use noirc_abi::input_parser::Format;
+
+        use super::resolve_input_format;
+
+        let dir = tempdir::TempDir::new("input_format").unwrap();
+        assert_eq!(resolve_input_format(dir.path(), "Prover", None).unwrap(), Format::Toml);
+
+        std::fs::write(dir.path().join("Prover.json"), "{}").unwrap();
+        assert_eq!(resolve_input_format(dir.path(), "Prover", None).unwrap(), Format::Json);
+
+        std::fs::write(dir.path().join("Prover.toml"), "").unwrap();
+        let err = resolve_input_format(dir.path(), "Prover", None).unwrap_err();
+        assert!(err.to_string().contains("found both Prover.toml and Prover.json"), "{}", err);
+
+        // An explicit format settles the ambiguity
+        let requested = resolve_input_format(dir.path(), "Prover", Some(Format::Json));
+        assert_eq!(requested.unwrap(), Format::Json);
+    }
+
+    #[test]
+    fn binary_commands_only_check_the_libraries_of_a_workspace() {
+        use crate::{backends::select_backend, workspace::select_packages};
+
+        use super::{compile_cmd::compile_circuit, run_on_packages};
+
+        let dir = tempdir::TempDir::new("workspace").unwrap();
+        std::fs::write(
+            dir.path().join("Nargo.toml"),
+            "[workspace]\nmembers = [\"circuits/a\", \"circuits/b\", \"libs/shared\"]\n",
+        )
+        .unwrap();
+        let package = |member: &str, dependencies: &str, file: &str, source: &str| {
+            let package_dir = dir.path().join(member);
+            std::fs::create_dir_all(package_dir.join("src")).unwrap();
+            let manifest = format!("[package]\nauthors = []\n\n[dependencies]\n{}", dependencies);
+            std::fs::write(package_dir.join("Nargo.toml"), manifest).unwrap();
+            std::fs::write(package_dir.join("src").join(file), source).unwrap();
+        };
+        let shared = "shared = { path = \"../../libs/shared\" }";
+        let main = "use dep::shared;\n\nfn main(x : Field) {\n    constrain shared::double(x) == x + x;\n}\n";
+        package("circuits/a", shared, "main.nr", main);
+        package("circuits/b", shared, "main.nr", main);
+        package("libs/shared", "", "lib.nr", "fn double(x : Field) -> Field {\n    x * 2\n}\n");
+
+        let packages = select_packages(dir.path(), None).unwrap();
+        let mut compiled = Vec::new();
+        run_on_packages(&packages, true, |package| {
+            let backend = select_backend(&package.dir, None)?;
+            compile_circuit(&backend, &package.dir, false)?;
+            compiled.push(package.name.clone());
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(compiled, vec!["a", "b"]);
+
+        // A library which is selected on its own cannot be compiled
+        let packages = select_packages(dir.path(), Some("shared")).unwrap();
+        let result = run_on_packages(&packages, true, |package| {
+            let backend = select_backend(&package.dir, None)?;
+            compile_circuit(&backend, &package.dir, false).map(|_| ())
+        });
+        assert!(result.is_err());
+    }
+
     #[test]
     fn compilation_fail() {
         let mut fail_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
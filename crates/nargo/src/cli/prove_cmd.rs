@@ -2,39 +2,50 @@ use std::{collections::BTreeMap, path::PathBuf};
 
 use acvm::acir::native_types::Witness;
 use acvm::FieldElement;
+use acvm::GateResolution;
 use acvm::ProofSystemCompiler;
-use acvm::{GateResolution, PartialWitnessGenerator};
 use clap::ArgMatches;
 use noirc_abi::AbiType;
-use noirc_abi::{input_parser::InputValue, Abi};
+use noirc_abi::{
+    input_parser::{FieldFormat, Format, InputValue},
+    Abi,
+};
 use std::path::Path;
 
+use crate::backends::{select_backend, ConcreteBackend};
 use crate::errors::CliError;
+use crate::workspace::SelectedPackage;
 
 use super::{
-    create_named_dir, write_to_file, PROOFS_DIR, PROOF_EXT, PROVER_INPUT_FILE, VERIFIER_INPUT_FILE,
+    create_named_dir, for_each_binary_package, requested_input_options, resolve_input_format,
+    write_to_file, InputOptions, PROOFS_DIR, PROOF_EXT, PROVER_INPUT_FILE, VERIFIER_INPUT_FILE,
 };
 
 pub(crate) fn run(args: ArgMatches) -> Result<(), CliError> {
     let args = args.subcommand_matches("prove").unwrap();
     let proof_name = args.value_of("proof_name").unwrap();
     let show_ssa = args.is_present("show-ssa");
-    prove(proof_name, show_ssa)
+    let input_options = requested_input_options(args);
+    for_each_binary_package(args, |package| {
+        prove(package, proof_name, args.value_of("backend"), show_ssa, input_options)
+    })
 }
 
 /// In Barretenberg, the proof system adds a zero witness in the first index,
 /// So when we add witness values, their index start from 1.
 const WITNESS_OFFSET: u32 = 1;
 
-fn prove(proof_name: &str, show_ssa: bool) -> Result<(), CliError> {
-    let curr_dir = std::env::current_dir().unwrap();
-    let mut proof_path = PathBuf::new();
-    proof_path.push(PROOFS_DIR);
-    let result = prove_with_path(proof_name, curr_dir, proof_path, show_ssa);
-    match result {
-        Ok(_) => Ok(()),
-        Err(e) => Err(e),
-    }
+fn prove(
+    package: &SelectedPackage,
+    proof_name: &str,
+    backend: Option<&str>,
+    show_ssa: bool,
+    input_options: InputOptions,
+) -> Result<(), CliError> {
+    let backend = select_backend(&package.dir, backend)?;
+    let proof_path = package.output_dir(PROOFS_DIR);
+    prove_with_path(&backend, proof_name, package.dir.clone(), proof_path, show_ssa, input_options)
+        .map(|_| ())
 }
 
 /// Ordering is important here, which is why we need the ABI to tell us what order to add the elements in
@@ -42,21 +53,12 @@ fn prove(proof_name: &str, show_ssa: bool) -> Result<(), CliError> {
 fn process_abi_with_input(
     abi: Abi,
     witness_map: &BTreeMap<String, InputValue>,
+    input_format: Format,
 ) -> Result<(BTreeMap<Witness, FieldElement>, Option<Witness>), CliError> {
     let mut solved_witness = BTreeMap::new();
 
     let mut index = 0;
     let mut return_witness = None;
-    let return_witness_len = if let Some(return_param) =
-        abi.parameters.iter().find(|x| x.0 == noirc_frontend::hir_def::function::MAIN_RETURN_NAME)
-    {
-        match &return_param.1 {
-            AbiType::Array { length, .. } => *length as u32,
-            AbiType::Integer { .. } | AbiType::Field(_) => 1,
-        }
-    } else {
-        0
-    };
     for (param_name, param_type) in abi.parameters.into_iter() {
         let value = witness_map
             .get(&param_name)
@@ -65,25 +67,11 @@ fn process_abi_with_input(
             })
             .clone();
 
-        if !value.matches_abi(param_type) {
-            return Err(CliError::Generic(format!("The parameters in the main do not match the parameters in the {}.toml file. \n Please check `{}` parameter ", PROVER_INPUT_FILE,param_name)));
+        if !value.matches_abi(&param_type) {
+            return Err(CliError::Generic(format!("The parameters in the main do not match the parameters in the {}.{} file. \n Please check `{}` parameter ", PROVER_INPUT_FILE, input_format.ext(), param_name)));
         }
 
         match value {
-            InputValue::Field(element) => {
-                let old_value =
-                    solved_witness.insert(Witness::new(index + WITNESS_OFFSET), element);
-                assert!(old_value.is_none());
-                index += 1;
-            }
-            InputValue::Vec(arr) => {
-                for element in arr {
-                    let old_value =
-                        solved_witness.insert(Witness::new(index + WITNESS_OFFSET), element);
-                    assert!(old_value.is_none());
-                    index += 1;
-                }
-            }
             InputValue::Undefined => {
                 assert_eq!(
                     param_name,
@@ -91,17 +79,17 @@ fn process_abi_with_input(
                     "input value {} is not defined",
                     param_name
                 );
+                // The witnesses of the return value are contiguous, so we only keep track of the first one
                 return_witness = Some(Witness::new(index + WITNESS_OFFSET));
-
-                //We do not support undefined arrays for now - TODO
-                if return_witness_len != 1 {
-                    return Err(CliError::Generic(
-                        "Values of array returned from main must be specified in prover toml file"
-                            .to_string(),
-                    ));
+                index += param_type.field_count() as u32;
+            }
+            value => {
+                for element in value.to_fields() {
+                    let old_value =
+                        solved_witness.insert(Witness::new(index + WITNESS_OFFSET), element);
+                    assert!(old_value.is_none());
+                    index += 1;
                 }
-                index += return_witness_len;
-                //XXX We do not support (yet) array of arrays
             }
         }
     }
@@ -109,25 +97,61 @@ fn process_abi_with_input(
 }
 
 pub fn compile_circuit_and_witness<P: AsRef<Path>>(
+    backend: &ConcreteBackend,
     program_dir: P,
     show_ssa: bool,
+    input_options: InputOptions,
 ) -> Result<(noirc_driver::CompiledProgram, BTreeMap<Witness, FieldElement>), CliError> {
-    let compiled_program = super::compile_cmd::compile_circuit(program_dir.as_ref(), show_ssa)?;
-    let solved_witness = solve_witness(program_dir, &compiled_program)?;
+    let compiled_program =
+        super::compile_cmd::compile_circuit(backend, program_dir.as_ref(), show_ssa)?;
+    let solved_witness = solve_witness(backend, program_dir, &compiled_program, input_options)?;
     Ok((compiled_program, solved_witness))
 }
 
 pub fn solve_witness<P: AsRef<Path>>(
+    backend: &ConcreteBackend,
     program_dir: P,
     compiled_program: &noirc_driver::CompiledProgram,
+    input_options: InputOptions,
 ) -> Result<BTreeMap<Witness, FieldElement>, CliError> {
+    let (solved_witness, _) = solve(backend, program_dir, compiled_program, input_options, true)?;
+    Ok(solved_witness)
+}
+
+/// Solves the witness using the values in the prover input file and
+/// returns it along with the value returned by `main`, if there is one.
+///
+/// Unlike `solve_witness`, this does not write the verifier input file.
+pub fn solve_witness_and_return_value<P: AsRef<Path>>(
+    backend: &ConcreteBackend,
+    program_dir: P,
+    compiled_program: &noirc_driver::CompiledProgram,
+    input_options: InputOptions,
+) -> Result<(BTreeMap<Witness, FieldElement>, Option<InputValue>), CliError> {
+    solve(backend, program_dir, compiled_program, input_options, false)
+}
+
+/// Solves the witness, writing the public inputs to the verifier input file if `export_verifier_inputs` is set.
+///
+/// The verifier input file is written in the same format as the prover input file,
+/// with field elements written as requested in `input_options`.
+fn solve<P: AsRef<Path>>(
+    backend: &ConcreteBackend,
+    program_dir: P,
+    compiled_program: &noirc_driver::CompiledProgram,
+    input_options: InputOptions,
+    export_verifier_inputs: bool,
+) -> Result<(BTreeMap<Witness, FieldElement>, Option<InputValue>), CliError> {
+    let input_format = resolve_input_format(&program_dir, PROVER_INPUT_FILE, input_options.format)?;
+
+    let abi = compiled_program.abi.as_ref().unwrap();
+
     // Parse the initial witness values
-    let witness_map = noirc_abi::input_parser::Format::Toml
-        .parse(&program_dir, PROVER_INPUT_FILE)
-        .map_err(CliError::from)?;
+    let witness_map =
+        input_format.parse(&program_dir, PROVER_INPUT_FILE, abi).map_err(CliError::from)?;
 
     // Check that enough witness values were supplied
-    let num_params = compiled_program.abi.as_ref().unwrap().num_parameters();
+    let num_params = abi.num_parameters();
     if num_params != witness_map.len() {
         panic!(
             "Expected {} number of values, but got {} number of values",
@@ -136,15 +160,10 @@ pub fn solve_witness<P: AsRef<Path>>(
         )
     }
     // Map initial witnesses with their values
-    let abi = compiled_program.abi.as_ref().unwrap();
     // Solve the remaining witnesses
-    let (mut solved_witness, rv) = process_abi_with_input(abi.clone(), &witness_map)?;
+    let (mut solved_witness, rv) = process_abi_with_input(abi.clone(), &witness_map, input_format)?;
 
-    let backend = crate::backends::ConcreteBackend;
     let solver_res = backend.solve(&mut solved_witness, compiled_program.circuit.gates.clone());
-    // (over)writes verifier.toml
-    export_public_inputs(rv, &solved_witness, &witness_map, abi, &program_dir)
-        .map_err(CliError::from)?;
 
     match solver_res {
             GateResolution::UnsupportedOpcode(opcode) => return Err(CliError::Generic(format!(
@@ -158,7 +177,50 @@ pub fn solve_witness<P: AsRef<Path>>(
             _ => unreachable!(),
         }
 
-    Ok(solved_witness)
+    if export_verifier_inputs {
+        // (over)writes the verifier input file
+        export_public_inputs(
+            rv,
+            &solved_witness,
+            &witness_map,
+            abi,
+            &program_dir,
+            input_format,
+            input_options.field_format,
+        )
+        .map_err(CliError::from)?;
+    }
+
+    let return_value = abi
+        .parameters
+        .iter()
+        .find(|(param_name, _)| param_name == noirc_frontend::hir_def::function::MAIN_RETURN_NAME)
+        .map(|(param_name, param_type)| {
+            fetch_input_value(param_name, param_type, rv, &solved_witness, &witness_map)
+        });
+
+    Ok((solved_witness, return_value))
+}
+
+/// Returns the value of a parameter given in the prover input file.
+/// If the value was left undefined (only allowed for the return value of `main`),
+/// it is read back from the solved witness instead.
+fn fetch_input_value(
+    param_name: &str,
+    param_type: &AbiType,
+    w_ret: Option<Witness>,
+    solved_witness: &BTreeMap<Witness, FieldElement>,
+    witness_map: &BTreeMap<String, InputValue>,
+) -> InputValue {
+    let v = &witness_map[param_name];
+    if matches!(*v, InputValue::Undefined) {
+        let w_ret = w_ret.unwrap();
+        let mut return_values = (0..param_type.field_count() as u32)
+            .map(|i| *solved_witness.get(&Witness::new(w_ret.0 + i)).unwrap());
+        InputValue::from_fields(param_type, &mut return_values)
+    } else {
+        v.clone()
+    }
 }
 
 fn export_public_inputs<P: AsRef<Path>>(
@@ -167,43 +229,32 @@ fn export_public_inputs<P: AsRef<Path>>(
     witness_map: &BTreeMap<String, InputValue>,
     abi: &Abi,
     path: P,
+    input_format: Format,
+    field_format: FieldFormat,
 ) -> Result<(), noirc_abi::errors::InputParserError> {
     // generate a name->value map for the public inputs, using the ABI and witness_map:
     let mut public_inputs = BTreeMap::new();
     for i in &abi.parameters {
         if i.1.is_public() {
-            let v = &witness_map[&i.0];
-
-            let iv = if matches!(*v, InputValue::Undefined) {
-                let w_ret = w_ret.unwrap();
-                match &i.1 {
-                    AbiType::Array { length, .. } => {
-                        let return_values = noirc_frontend::util::vecmap(0..*length, |i| {
-                            *solved_witness.get(&Witness::new(w_ret.0 + i as u32)).unwrap()
-                        });
-                        InputValue::Vec(return_values)
-                    }
-                    _ => InputValue::Field(*solved_witness.get(&w_ret).unwrap()),
-                }
-            } else {
-                v.clone()
-            };
+            let iv = fetch_input_value(&i.0, &i.1, w_ret, solved_witness, witness_map);
             public_inputs.insert(i.0.clone(), iv);
         }
     }
-    //serialise public inputs into verifier.toml
-    noirc_abi::input_parser::Format::Toml.serialise(&path, VERIFIER_INPUT_FILE, &public_inputs)
+    //serialise public inputs into the verifier input file
+    input_format.serialise(&path, VERIFIER_INPUT_FILE, &public_inputs, abi, field_format)
 }
 
 pub fn prove_with_path<P: AsRef<Path>>(
+    backend: &ConcreteBackend,
     proof_name: &str,
     program_dir: P,
     proof_dir: P,
     show_ssa: bool,
+    input_options: InputOptions,
 ) -> Result<PathBuf, CliError> {
-    let (compiled_program, solved_witness) = compile_circuit_and_witness(program_dir, show_ssa)?;
+    let (compiled_program, solved_witness) =
+        compile_circuit_and_witness(backend, program_dir, show_ssa, input_options)?;
 
-    let backend = crate::backends::ConcreteBackend;
     let proof = backend.prove_with_meta(compiled_program.circuit, solved_witness);
 
     let mut proof_path = create_named_dir(proof_dir.as_ref(), "proof");
@@ -0,0 +1,161 @@
+use std::{collections::BTreeMap, io::Write, path::Path};
+
+use acvm::{GateResolution, ProofSystemCompiler};
+use clap::ArgMatches;
+use noirc_driver::Driver;
+use noirc_frontend::node_interner::FuncId;
+use noirc_frontend::token::{Attribute, TestScope};
+use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
+
+use crate::{
+    backends::{select_backend, ConcreteBackend},
+    errors::CliError,
+    resolver::Resolver,
+};
+
+use super::{add_std_lib, check_crate, configure_driver, for_each_package, print_diagnostics};
+
+pub(crate) fn run(args: ArgMatches) -> Result<(), CliError> {
+    let args = args.subcommand_matches("test").unwrap();
+    let test_name = args.value_of("test_name").unwrap_or("");
+    let show_ssa = args.is_present("show-ssa");
+
+    for_each_package(args, |package| {
+        let backend = select_backend(&package.dir, args.value_of("backend"))?;
+        run_tests(&backend, &package.dir, test_name, show_ssa)
+    })
+}
+
+fn run_tests(
+    backend: &ConcreteBackend,
+    program_dir: &Path,
+    test_name: &str,
+    show_ssa: bool,
+) -> Result<(), CliError> {
+    let mut driver = Resolver::resolve_root_config(program_dir)?;
+    add_std_lib(&mut driver);
+    configure_driver(&mut driver);
+    check_crate(&mut driver)?;
+
+    let test_functions = driver.get_all_test_functions_in_crate_matching(test_name);
+    println!("Running {} test functions...", test_functions.len());
+    let mut failing = 0;
+
+    let writer = StandardStream::stderr(ColorChoice::Always);
+    let mut writer = writer.lock();
+
+    for test_function in test_functions {
+        let test_name = driver.function_name(test_function);
+        write!(writer, "Testing {}... ", test_name).expect("cannot write to stderr");
+        writer.flush().expect("cannot flush stderr");
+
+        match run_test(backend, &driver, test_function, show_ssa) {
+            Ok(_) => {
+                writer.set_color(ColorSpec::new().set_fg(Some(Color::Green))).ok();
+                writeln!(writer, "ok").expect("cannot write to stderr");
+            }
+            Err(err) => {
+                writer.set_color(ColorSpec::new().set_fg(Some(Color::Red))).ok();
+                writeln!(writer, "FAIL\n{}", err).expect("cannot write to stderr");
+                failing += 1;
+            }
+        }
+        writer.reset().ok();
+    }
+
+    if failing == 0 {
+        writer.set_color(ColorSpec::new().set_fg(Some(Color::Green))).ok();
+        writeln!(writer, "All tests passed").expect("cannot write to stderr");
+        writer.reset().ok();
+        Ok(())
+    } else {
+        let plural = if failing == 1 { "" } else { "s" };
+        Err(CliError::Generic(format!("{} test{} failed", failing, plural)))
+    }
+}
+
+/// Compiles the test function as if it were `main` and runs the partial witness
+/// generator over the resulting circuit. No proof is created.
+fn run_test(
+    backend: &ConcreteBackend,
+    driver: &Driver,
+    test_function: FuncId,
+    show_ssa: bool,
+) -> Result<(), CliError> {
+    let func_meta = driver.function_meta(test_function);
+    if !func_meta.parameters.is_empty() {
+        return Err(CliError::Generic("test functions cannot take any parameters".to_owned()));
+    }
+    let should_fail = func_meta.attributes == Some(Attribute::Test(TestScope::ShouldFail));
+
+    let program = match driver.compile_no_check(backend.np_language(), show_ssa, test_function) {
+        Ok(program) => program,
+        // Constraints which are known to fail at compile time are reported as errors
+        // by the compiler, which is the expected outcome of a `should_fail` test.
+        Err(_) if should_fail => return Ok(()),
+        Err(diagnostics) => {
+            print_diagnostics(driver, &diagnostics);
+            return Err(CliError::Generic("test failed to compile".to_owned()));
+        }
+    };
+
+    let mut solved_witness = BTreeMap::new();
+    match (backend.solve(&mut solved_witness, program.circuit.gates), should_fail) {
+        (GateResolution::Resolved, false) | (GateResolution::UnsatisfiedConstrain, true) => Ok(()),
+        (GateResolution::Resolved, true) => {
+            Err(CliError::Generic("test passed but was expected to fail".to_owned()))
+        }
+        (GateResolution::UnsatisfiedConstrain, false) => {
+            Err(CliError::Generic("could not satisfy all constraints".to_owned()))
+        }
+        (GateResolution::UnsupportedOpcode(opcode), _) => Err(CliError::Generic(format!(
+            "backend does not currently support the {} opcode. ACVM does not currently fall back to arithmetic gates.",
+            opcode
+        ))),
+        (GateResolution::UnknownError(msg), _) => Err(CliError::Generic(msg)),
+        (GateResolution::Skip, _) => {
+            Err(CliError::Generic("could not solve all of the gates".to_owned()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backends::ConcreteBackend;
+
+    const TEST_DATA_DIR: &str = "tests/test_cmd_data";
+
+    #[test]
+    fn pass() {
+        let mut pass_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        pass_dir.push(&format!("{TEST_DATA_DIR}/pass"));
+
+        let backend = ConcreteBackend::default_backend();
+        let paths = std::fs::read_dir(pass_dir).unwrap();
+        for path in paths.flatten() {
+            let path = path.path();
+            assert!(
+                super::run_tests(&backend, &path, "", false).is_ok(),
+                "path: {}",
+                path.display()
+            );
+        }
+    }
+
+    #[test]
+    fn fail() {
+        let mut fail_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        fail_dir.push(&format!("{TEST_DATA_DIR}/fail"));
+
+        let backend = ConcreteBackend::default_backend();
+        let paths = std::fs::read_dir(fail_dir).unwrap();
+        for path in paths.flatten() {
+            let path = path.path();
+            assert!(
+                super::run_tests(&backend, &path, "", false).is_err(),
+                "path: {}",
+                path.display()
+            );
+        }
+    }
+}
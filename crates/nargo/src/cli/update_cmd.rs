@@ -0,0 +1,10 @@
+use clap::ArgMatches;
+
+use crate::{errors::CliError, lockfile::LOCK_FILE, resolver::Resolver};
+
+pub(crate) fn run(_args: ArgMatches) -> Result<(), CliError> {
+    let package_dir = std::env::current_dir().unwrap();
+    Resolver::update_lockfile(&package_dir)?;
+    println!("{} successfully updated", LOCK_FILE);
+    Ok(())
+}
@@ -0,0 +1,24 @@
+use clap::ArgMatches;
+
+use crate::{
+    errors::CliError,
+    resolver::Resolver,
+    vendor::{check_vendored_packages, vendor_packages, VENDOR_DIR},
+};
+
+pub(crate) fn run(args: ArgMatches) -> Result<(), CliError> {
+    let args = args.subcommand_matches("vendor").unwrap();
+    let current_dir = std::env::current_dir().unwrap();
+
+    // The dependencies are always resolved from their sources, rather than from the vendored copies.
+    // The members of a workspace are vendored together, into the root of the workspace.
+    let (package_dir, packages) = Resolver::resolve_sources(&current_dir)?;
+    if args.is_present("check") {
+        check_vendored_packages(&package_dir, &packages)?;
+        println!("The vendored dependencies match their sources");
+    } else {
+        let num_vendored = vendor_packages(&package_dir, &packages)?;
+        println!("Vendored {} dependencies into {}", num_vendored, VENDOR_DIR);
+    }
+    Ok(())
+}
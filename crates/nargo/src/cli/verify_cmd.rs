@@ -1,36 +1,68 @@
 use super::compile_cmd::compile_circuit;
-use super::{PROOFS_DIR, PROOF_EXT, VERIFIER_INPUT_FILE};
-use crate::errors::CliError;
+use super::{
+    for_each_binary_package, requested_input_format, resolve_input_format, PROOFS_DIR, PROOF_EXT,
+    VERIFIER_INPUT_FILE,
+};
+use crate::{
+    artifact::ProgramArtifact,
+    backends::{select_backend, ConcreteBackend},
+    errors::CliError,
+    workspace::SelectedPackage,
+};
+use acvm::acir::circuit::Circuit;
 use acvm::{FieldElement, ProofSystemCompiler};
 use clap::ArgMatches;
-use noirc_abi::{input_parser::InputValue, Abi};
-use std::{collections::BTreeMap, path::Path, path::PathBuf};
+use noirc_abi::{
+    input_parser::{Format, InputValue},
+    Abi,
+};
+use std::{collections::BTreeMap, path::Path};
 
 pub(crate) fn run(args: ArgMatches) -> Result<(), CliError> {
-    let proof_name = args.subcommand_matches("verify").unwrap().value_of("proof").unwrap();
-    let mut proof_path = std::path::PathBuf::new();
-    proof_path.push(Path::new(PROOFS_DIR));
+    let args = args.subcommand_matches("verify").unwrap();
+    let input_format = requested_input_format(args);
 
-    proof_path.push(Path::new(proof_name));
-    proof_path.set_extension(PROOF_EXT);
-
-    let result = verify(proof_name)?;
-    println!("Proof verified : {}\n", result);
-    Ok(())
+    match args.value_of("artifact") {
+        Some(artifact_path) => {
+            // clap ensures that a proof path is supplied alongside an artifact
+            let proof_path = args.value_of("proof-path").unwrap();
+            let curr_dir = std::env::current_dir().unwrap();
+            let result = verify_with_artifact(
+                args.value_of("backend"),
+                Path::new(artifact_path),
+                Path::new(proof_path),
+                curr_dir.as_path(),
+                input_format,
+            )?;
+            println!("Proof verified : {}\n", result);
+            Ok(())
+        }
+        None => for_each_binary_package(args, |package| {
+            let proof_name = args.value_of("proof").unwrap();
+            let result = verify(package, proof_name, args.value_of("backend"), input_format)?;
+            println!("Proof verified : {}\n", result);
+            Ok(())
+        }),
+    }
 }
 
-fn verify(proof_name: &str) -> Result<bool, CliError> {
-    let curr_dir = std::env::current_dir().unwrap();
-    let mut proof_path = PathBuf::new(); //or cur_dir?
-    proof_path.push(PROOFS_DIR);
+fn verify(
+    package: &SelectedPackage,
+    proof_name: &str,
+    backend: Option<&str>,
+    input_format: Option<Format>,
+) -> Result<bool, CliError> {
+    let backend = select_backend(&package.dir, backend)?;
+    let mut proof_path = package.output_dir(PROOFS_DIR);
     proof_path.push(Path::new(proof_name));
     proof_path.set_extension(PROOF_EXT);
-    verify_with_path(&curr_dir, &proof_path, false)
+    verify_with_path(&backend, &package.dir, &proof_path, false, input_format)
 }
 
 fn process_abi_with_verifier_input(
     abi: Abi,
     pi_map: BTreeMap<String, InputValue>,
+    input_format: Format,
 ) -> Result<Vec<FieldElement>, CliError> {
     let mut public_inputs = Vec::with_capacity(pi_map.len());
 
@@ -42,19 +74,20 @@ fn process_abi_with_verifier_input(
             })
             .clone();
 
-        if !value.matches_abi(param_type) {
-            return Err(CliError::Generic(format!("The parameters in the main do not match the parameters in the {}.toml file. \n Please check `{}` parameter. ", VERIFIER_INPUT_FILE,param_name)));
+        if !value.matches_abi(&param_type) {
+            return Err(CliError::Generic(format!("The parameters in the main do not match the parameters in the {}.{} file. \n Please check `{}` parameter. ", VERIFIER_INPUT_FILE, input_format.ext(), param_name)));
         }
 
         match value {
-            InputValue::Field(elem) => public_inputs.push(elem),
-            InputValue::Vec(vec_elem) => public_inputs.extend(vec_elem),
             InputValue::Undefined => {
                 return Err(CliError::Generic(format!(
-                    "The parameter {} is not defined in the {}.toml file.",
-                    param_name, VERIFIER_INPUT_FILE
+                    "The parameter {} is not defined in the {}.{} file.",
+                    param_name,
+                    VERIFIER_INPUT_FILE,
+                    input_format.ext()
                 )))
             }
+            value => public_inputs.extend(value.to_fields()),
         }
     }
 
@@ -62,41 +95,186 @@ fn process_abi_with_verifier_input(
 }
 
 pub fn verify_with_path<P: AsRef<Path>>(
+    backend: &ConcreteBackend,
     program_dir: P,
     proof_path: P,
     show_ssa: bool,
+    input_format: Option<Format>,
 ) -> Result<bool, CliError> {
-    let compiled_program = compile_circuit(program_dir.as_ref(), show_ssa)?;
+    let compiled_program = compile_circuit(backend, program_dir.as_ref(), show_ssa)?;
+    let abi = compiled_program.abi.unwrap();
+
+    verify_proof(backend, compiled_program.circuit, abi, proof_path, program_dir, input_format)
+}
 
-    let public_abi = compiled_program.abi.clone().unwrap().public_abi();
+/// Verifies a proof using the circuit and ABI stored in a build artifact,
+/// so that neither the program source nor the compiler is needed.
+///
+/// The public inputs are read from the verifier input file in `verifier_input_dir`.
+/// The proof is verified with the backend the artifact was compiled for,
+/// and requesting another backend is an error.
+pub fn verify_with_artifact<P: AsRef<Path>>(
+    backend: Option<&str>,
+    artifact_path: P,
+    proof_path: P,
+    verifier_input_dir: P,
+    input_format: Option<Format>,
+) -> Result<bool, CliError> {
+    let artifact = ProgramArtifact::read(&artifact_path)?;
+    let backend = ConcreteBackend::from_name(backend.unwrap_or(&artifact.backend))?;
+    if backend.name() != artifact.backend {
+        return Err(CliError::Generic(format!(
+            "the artifact at {} was compiled for the {} backend, and cannot be verified with the {} backend",
+            artifact_path.as_ref().display(),
+            artifact.backend,
+            backend.name()
+        )));
+    }
+
+    verify_proof(
+        &backend,
+        artifact.circuit,
+        artifact.abi,
+        proof_path,
+        verifier_input_dir,
+        input_format,
+    )
+}
+
+fn verify_proof<P: AsRef<Path>>(
+    backend: &ConcreteBackend,
+    circuit: Circuit,
+    abi: Abi,
+    proof_path: P,
+    verifier_input_dir: P,
+    input_format: Option<Format>,
+) -> Result<bool, CliError> {
+    let input_format =
+        resolve_input_format(&verifier_input_dir, VERIFIER_INPUT_FILE, input_format)?;
+    let public_abi = abi.public_abi();
     let num_pub_params = public_abi.num_parameters();
     let mut public_inputs = BTreeMap::new();
     if num_pub_params != 0 {
-        let curr_dir = program_dir;
-        public_inputs = noirc_abi::input_parser::Format::Toml
-            .parse(curr_dir, VERIFIER_INPUT_FILE)
+        public_inputs = input_format
+            .parse(verifier_input_dir, VERIFIER_INPUT_FILE, &public_abi)
             .map_err(CliError::from)?;
     }
 
     if num_pub_params != public_inputs.len() {
         // return Err(CliError::Generic(format!("")));
         panic!(
-            "Expected {} number of values in {}.toml, but got {} number of values",
+            "Expected {} number of values in {}.{}, but got {} number of values",
             num_pub_params,
             VERIFIER_INPUT_FILE,
+            input_format.ext(),
             public_inputs.len()
         )
     }
 
-    let public_inputs = process_abi_with_verifier_input(public_abi, public_inputs)?;
+    let public_inputs = process_abi_with_verifier_input(public_abi, public_inputs, input_format)?;
 
-    // XXX: Instead of unwrap, return a PathNotValidError
-    let proof_hex: Vec<_> = std::fs::read(&proof_path).unwrap();
-    // XXX: Instead of unwrap, return a ProofNotValidError
-    let proof = hex::decode(proof_hex).unwrap();
+    let proof_path = proof_path.as_ref();
+    let proof_hex: Vec<_> =
+        std::fs::read(proof_path).map_err(|_| CliError::PathNotValid(proof_path.to_path_buf()))?;
+    let proof =
+        hex::decode(proof_hex).map_err(|_| CliError::InvalidProof(proof_path.to_path_buf()))?;
 
-    let backend = crate::backends::ConcreteBackend;
-    let valid_proof = backend.verify_from_cs(&proof, public_inputs, compiled_program.circuit);
+    let valid_proof = backend.verify_from_cs(&proof, public_inputs, circuit);
 
     Ok(valid_proof)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::verify_with_artifact;
+    use crate::{
+        artifact::ProgramArtifact,
+        backends::select_backend,
+        cli::{compile_cmd::compile_circuit, prove_cmd::prove_with_path, InputOptions},
+        errors::CliError,
+    };
+
+    #[test]
+    fn verifies_with_an_artifact_without_the_sources() {
+        let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/test_data/main_return");
+        let dir = tempdir::TempDir::new("verify_artifact").unwrap();
+        let program_dir = dir.path().join("main_return");
+        std::fs::create_dir_all(program_dir.join("src")).unwrap();
+        for file in ["Nargo.toml", "Prover.toml", "src/main.nr"] {
+            std::fs::copy(fixture.join(file), program_dir.join(file)).unwrap();
+        }
+
+        let backend = select_backend(&program_dir, None).unwrap();
+        let compiled_program = compile_circuit(&backend, &program_dir, false).unwrap();
+        let artifact = ProgramArtifact::new(
+            backend.name(),
+            compiled_program.circuit,
+            compiled_program.abi.unwrap(),
+        );
+        let artifact_path = dir.path().join("main_return.json");
+        std::fs::write(&artifact_path, artifact.to_json()).unwrap();
+
+        let proof_path = prove_with_path(
+            &backend,
+            "p",
+            program_dir.clone(),
+            dir.path().join("proofs"),
+            false,
+            InputOptions::default(),
+        )
+        .unwrap();
+
+        // Only the artifact, the proof and the public inputs are left
+        let verifier_dir = dir.path().join("verifier");
+        std::fs::create_dir(&verifier_dir).unwrap();
+        std::fs::copy(program_dir.join("Verifier.toml"), verifier_dir.join("Verifier.toml"))
+            .unwrap();
+        std::fs::remove_dir_all(&program_dir).unwrap();
+
+        assert!(
+            verify_with_artifact(None, &artifact_path, &proof_path, &verifier_dir, None).unwrap()
+        );
+        assert!(verify_with_artifact(
+            Some(backend.name()),
+            &artifact_path,
+            &proof_path,
+            &verifier_dir,
+            None
+        )
+        .unwrap());
+
+        // The artifact cannot be verified with another backend than the one it was compiled for
+        let mut other_backend = ProgramArtifact::read(&artifact_path).unwrap();
+        other_backend.backend = "other".to_owned();
+        let other_artifact_path = dir.path().join("other_backend.json");
+        std::fs::write(&other_artifact_path, other_backend.to_json()).unwrap();
+        let err = verify_with_artifact(
+            Some(backend.name()),
+            &other_artifact_path,
+            &proof_path,
+            &verifier_dir,
+            None,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("compiled for the other backend"), "{}", err);
+
+        // A corrupted artifact is reported rather than verified against
+        std::fs::write(&artifact_path, &artifact.to_json()[1..]).unwrap();
+        let err = verify_with_artifact(None, &artifact_path, &proof_path, &verifier_dir, None)
+            .unwrap_err();
+        assert!(err.to_string().contains("badly formed"), "{}", err);
+        std::fs::write(&artifact_path, artifact.to_json()).unwrap();
+
+        // Missing and corrupted proofs are reported rather than panicking
+        let missing_proof = dir.path().join("proofs/missing.proof");
+        let err = verify_with_artifact(None, &artifact_path, &missing_proof, &verifier_dir, None)
+            .unwrap_err();
+        assert!(matches!(err, CliError::PathNotValid(_)), "{}", err);
+        std::fs::write(&proof_path, "not hex").unwrap();
+        let err = verify_with_artifact(None, &artifact_path, &proof_path, &verifier_dir, None)
+            .unwrap_err();
+        assert!(matches!(err, CliError::InvalidProof(_)), "{}", err);
+    }
+}
@@ -1,11 +1,30 @@
 use noirc_abi::errors::InputParserError;
 use std::io::Write;
+use std::path::PathBuf;
 use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
 
 #[derive(Debug)]
 pub enum CliError {
     Generic(String),
     DestinationAlreadyExists(String),
+    GitCloneFailed {
+        url: String,
+        tag: String,
+        reason: String,
+    },
+    GitTagNotFound {
+        url: String,
+        tag: String,
+    },
+    IncompleteGitCheckout(PathBuf),
+    /// The git dependencies which are not in the cache while nargo is offline
+    MissingOfflineDependencies(Vec<String>),
+    /// A file which was expected to exist could not be read
+    PathNotValid(PathBuf),
+    /// The proof at the path is not hex encoded
+    InvalidProof(PathBuf),
+    /// The number of errors a package failed to compile with, the errors are reported as they are found
+    CompilationFailed(usize),
 }
 
 impl CliError {
@@ -13,6 +32,7 @@ impl CliError {
         match self {
             CliError::Generic(msg) => CliError::write_msg_exit(msg),
             CliError::DestinationAlreadyExists(msg) => CliError::write_msg_exit(msg),
+            err => CliError::write_msg_exit(&err.to_string()),
         }
     }
 
@@ -23,7 +43,44 @@ impl CliError {
             .expect("cannot set color for stderr in StandardStream");
         writeln!(&mut stderr, "{}", msg).expect("cannot write to stderr");
 
-        std::process::exit(0)
+        std::process::exit(1)
+    }
+}
+
+impl std::fmt::Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CliError::Generic(msg) => write!(f, "{}", msg),
+            CliError::DestinationAlreadyExists(msg) => write!(f, "{}", msg),
+            CliError::GitCloneFailed { url, tag, reason } => {
+                write!(f, "could not clone the tag {} of {}: {}", tag, url, reason)
+            }
+            CliError::GitTagNotFound { url, tag } => {
+                write!(f, "the repository {} does not have a tag or branch named {}", url, tag)
+            }
+            CliError::IncompleteGitCheckout(path) => {
+                write!(f, "the checkout in {} is incomplete", path.display())
+            }
+            CliError::MissingOfflineDependencies(deps) => {
+                write!(f, "cannot download these git dependencies while offline:")?;
+                for dep in deps {
+                    write!(f, "\n    {}", dep)?;
+                }
+                write!(
+                    f,
+                    "\nremove --offline to download them into {}",
+                    crate::nargo_crates().display()
+                )
+            }
+            CliError::PathNotValid(path) => write!(f, "{} is not a valid path", path.display()),
+            CliError::InvalidProof(path) => {
+                write!(f, "the proof in {} is not a valid hex encoded proof", path.display())
+            }
+            CliError::CompilationFailed(error_count) => {
+                let plural = if *error_count == 1 { "" } else { "s" };
+                write!(f, "error: aborting due to {} previous error{}", error_count, plural)
+            }
+        }
     }
 }
 
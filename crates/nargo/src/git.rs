@@ -1,4 +1,19 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::errors::CliError;
+
+/// Whether git dependencies must be taken from the cache, without using the network
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+pub(crate) fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+pub(crate) fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
 
 pub fn git_dep_location(base: &url::Url, tag: &str) -> PathBuf {
     let folder_name = super::resolver::resolve_folder_name(base, tag);
@@ -6,26 +21,114 @@ pub fn git_dep_location(base: &url::Url, tag: &str) -> PathBuf {
     super::nargo_crates().join(folder_name)
 }
 
+/// Returns the location of the checkout of the dependency in the cache,
+/// or an error if it must be downloaded while nargo is offline
+pub fn cached_git_repo(url: &str, tag: &str) -> Result<PathBuf, CliError> {
+    let base = parse_url(url)?;
+    let loc = git_dep_location(&base, tag);
+    if is_complete_checkout(&loc) {
+        Ok(loc)
+    } else {
+        Err(CliError::MissingOfflineDependencies(vec![format!("{} (tag {})", url, tag)]))
+    }
+}
+
 /// XXX: I'd prefer to use a GitHub library however, there
 /// does not seem to be an easy way to download a repo at a specific
 /// tag
 /// github-rs looks promising, however it seems to require an API token
 ///
 /// One advantage of using "git clone" is that there is effectively no rate limit
-pub fn clone_git_repo(url: &str, tag: &str) -> Result<PathBuf, String> {
-    use std::process::Command;
-
-    let base = match url::Url::parse(url) {
-        Ok(base) => base,
-        Err(err) => return Err(err.to_string()),
-    };
+pub fn clone_git_repo(url: &str, tag: &str) -> Result<PathBuf, CliError> {
+    let base = parse_url(url)?;
+    let loc = git_dep_location(&base, tag);
+    fetch_git_repo(&base, tag, &loc)?;
+    Ok(loc)
+}
 
+/// Fetches the dependency again, so that a tag which was moved resolves to its new commit.
+///
+/// The cached checkout is shared by every package on the machine, so it is only replaced
+/// once the new checkout is complete, and is kept as it is if fetching fails.
+pub fn update_git_repo(url: &str, tag: &str) -> Result<PathBuf, CliError> {
+    let base = parse_url(url)?;
     let loc = git_dep_location(&base, tag);
-    if loc.exists() {
-        return Ok(loc);
+    refetch_git_repo(&base, tag, &loc)?;
+    Ok(loc)
+}
+
+fn parse_url(url: &str) -> Result<url::Url, CliError> {
+    url::Url::parse(url)
+        .map_err(|err| CliError::Generic(format!("invalid git url {}: {}", url, err)))
+}
+
+/// Clones the repository at `base` into `loc`, unless a complete checkout is already there.
+///
+/// The repository is first cloned next to `loc` and only moved into place once the clone
+/// succeeded, so a failed or interrupted clone never leaves a partial checkout in the cache.
+fn fetch_git_repo(base: &url::Url, tag: &str, loc: &Path) -> Result<(), CliError> {
+    if is_complete_checkout(loc) {
+        return Ok(());
+    }
+    // An update which was interrupted after moving the previous checkout aside is undone
+    let previous = sibling(loc, ".previous");
+    if is_complete_checkout(&previous) {
+        remove_if_exists(loc)?;
+        return rename(&previous, loc);
     }
+    // A partial checkout left behind by an older version of nargo is downloaded again
+    remove_if_exists(loc)?;
 
-    Command::new("git")
+    let staging = clone_into_staging(base, tag, loc)?;
+    rename(&staging, loc)
+}
+
+/// Clones the repository at `base` again and replaces the checkout in `loc` with it,
+/// keeping the checkout in `loc` if the clone fails
+fn refetch_git_repo(base: &url::Url, tag: &str, loc: &Path) -> Result<(), CliError> {
+    if !is_complete_checkout(loc) {
+        return fetch_git_repo(base, tag, loc);
+    }
+    let staging = clone_into_staging(base, tag, loc)?;
+
+    // The previous checkout is moved aside rather than removed, so that it can be restored
+    // if the new one cannot be moved into place
+    let previous = sibling(loc, ".previous");
+    remove_if_exists(&previous)?;
+    rename(loc, &previous)?;
+    if let Err(err) = rename(&staging, loc) {
+        rename(&previous, loc)?;
+        remove_if_exists(&staging)?;
+        return Err(err);
+    }
+    remove_if_exists(&previous)
+}
+
+/// Returns the path next to `loc` which has `suffix` appended to its name
+fn sibling(loc: &Path, suffix: &str) -> PathBuf {
+    let mut name = loc.file_name().expect("ice: checkouts are not at the root").to_owned();
+    name.push(suffix);
+    loc.with_file_name(name)
+}
+
+fn rename(from: &Path, to: &Path) -> Result<(), CliError> {
+    std::fs::rename(from, to).map_err(|err| {
+        CliError::Generic(format!("could not move the checkout to {}: {}", to.display(), err))
+    })
+}
+
+/// Clones the repository at `base` into the staging directory next to `loc`, and returns it
+/// once the clone is complete. Nothing is left behind when the clone fails.
+fn clone_into_staging(base: &url::Url, tag: &str, loc: &Path) -> Result<PathBuf, CliError> {
+    let staging = sibling(loc, ".partial");
+    remove_if_exists(&staging)?;
+    if let Some(parent) = staging.parent() {
+        std::fs::create_dir_all(parent).map_err(|err| {
+            CliError::Generic(format!("could not create {}: {}", parent.display(), err))
+        })?;
+    }
+
+    let output = Command::new("git")
         .arg("-c")
         .arg("advice.detachedHead=false")
         .arg("clone")
@@ -34,9 +137,187 @@ pub fn clone_git_repo(url: &str, tag: &str) -> Result<PathBuf, String> {
         .arg("--branch")
         .arg(tag)
         .arg(base.as_str())
-        .arg(&loc)
-        .status()
-        .expect("git clone command failed to start");
+        .arg(&staging)
+        .output()
+        .map_err(|err| CliError::Generic(format!("git clone command failed to start: {}", err)))?;
 
-    Ok(loc)
+    if !output.status.success() {
+        remove_if_exists(&staging)?;
+        if remote_is_reachable(base) && !remote_has_ref(base, tag) {
+            return Err(CliError::GitTagNotFound { url: base.to_string(), tag: tag.to_owned() });
+        }
+        return Err(CliError::GitCloneFailed {
+            url: base.to_string(),
+            tag: tag.to_owned(),
+            reason: String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+        });
+    }
+
+    if !is_complete_checkout(&staging) {
+        remove_if_exists(&staging)?;
+        return Err(CliError::IncompleteGitCheckout(loc.to_path_buf()));
+    }
+    Ok(staging)
+}
+
+/// A checkout is complete once git can resolve the commit it is on
+fn is_complete_checkout(loc: &Path) -> bool {
+    loc.join(".git").exists() && head_commit(loc).is_ok()
+}
+
+fn remove_if_exists(path: &Path) -> Result<(), CliError> {
+    if !path.exists() {
+        return Ok(());
+    }
+    std::fs::remove_dir_all(path)
+        .map_err(|err| CliError::Generic(format!("could not remove {}: {}", path.display(), err)))
+}
+
+fn remote_is_reachable(base: &url::Url) -> bool {
+    Command::new("git")
+        .arg("ls-remote")
+        .arg(base.as_str())
+        .output()
+        .map_or(false, |output| output.status.success())
+}
+
+fn remote_has_ref(base: &url::Url, tag: &str) -> bool {
+    Command::new("git")
+        .arg("ls-remote")
+        .arg("--exit-code")
+        .arg(base.as_str())
+        .arg(tag)
+        .output()
+        .map_or(false, |output| output.status.success())
+}
+
+/// Returns the hash of the commit which is checked out in the repository at `repo_path`
+pub fn head_commit(repo_path: &Path) -> Result<String, String> {
+    let output = Command::new("git")
+        .arg("rev-parse")
+        .arg("HEAD")
+        .current_dir(repo_path)
+        .output()
+        .map_err(|err| format!("git rev-parse command failed to start: {}", err))?;
+    if !output.status.success() {
+        return Err(format!(
+            "could not find the commit checked out in {}: {}",
+            repo_path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::process::Command;
+
+    use super::{fetch_git_repo, head_commit, refetch_git_repo};
+    use crate::errors::CliError;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(&["-c", "user.name=nargo", "-c", "user.email=nargo@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .expect("git must be installed to run the tests")
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    /// Creates a bare repository containing a library tagged `v0.1.0`, to be used as a remote
+    fn local_remote(root: &Path) -> url::Url {
+        let work = root.join("work");
+        std::fs::create_dir_all(work.join("src")).unwrap();
+        std::fs::write(work.join("Nargo.toml"), "[package]\nauthors = []\n\n[dependencies]\n")
+            .unwrap();
+        std::fs::write(work.join("src/lib.nr"), "fn foo() {}").unwrap();
+        git(&work, &["init", "--quiet"]);
+        git(&work, &["add", "."]);
+        git(&work, &["commit", "--quiet", "-m", "initial commit"]);
+        git(&work, &["tag", "v0.1.0"]);
+        git(root, &["clone", "--quiet", "--bare", "work", "remote.git"]);
+        url::Url::from_file_path(root.join("remote.git")).unwrap()
+    }
+
+    #[test]
+    fn clones_tags_from_a_remote() {
+        let dir = tempdir::TempDir::new("git_remote").unwrap();
+        let remote = local_remote(dir.path());
+        let loc = dir.path().join("cache/dep");
+
+        fetch_git_repo(&remote, "v0.1.0", &loc).unwrap();
+        assert!(loc.join("src/lib.nr").exists());
+        assert!(!dir.path().join("cache/dep.partial").exists());
+
+        // The cached checkout is reused
+        fetch_git_repo(&remote, "v0.1.0", &loc).unwrap();
+    }
+
+    #[test]
+    fn missing_tags_are_cleaned_up() {
+        let dir = tempdir::TempDir::new("git_remote").unwrap();
+        let remote = local_remote(dir.path());
+        let loc = dir.path().join("cache/dep");
+
+        let err = fetch_git_repo(&remote, "v9.9.9", &loc).unwrap_err();
+        assert!(matches!(err, CliError::GitTagNotFound { .. }), "{}", err);
+        assert!(!loc.exists());
+        assert!(!dir.path().join("cache/dep.partial").exists());
+    }
+
+    #[test]
+    fn unreachable_remotes_fail() {
+        let dir = tempdir::TempDir::new("git_remote").unwrap();
+        let remote = url::Url::from_file_path(dir.path().join("missing.git")).unwrap();
+        let loc = dir.path().join("cache/dep");
+
+        let err = fetch_git_repo(&remote, "v0.1.0", &loc).unwrap_err();
+        assert!(matches!(err, CliError::GitCloneFailed { .. }), "{}", err);
+        assert!(!loc.exists());
+    }
+
+    #[test]
+    fn partial_checkouts_are_replaced() {
+        let dir = tempdir::TempDir::new("git_remote").unwrap();
+        let remote = local_remote(dir.path());
+        let loc = dir.path().join("cache/dep");
+        std::fs::create_dir_all(&loc).unwrap();
+        std::fs::write(loc.join("Nargo.toml"), "").unwrap();
+
+        fetch_git_repo(&remote, "v0.1.0", &loc).unwrap();
+        assert!(loc.join("src/lib.nr").exists());
+    }
+
+    #[test]
+    fn updates_replace_the_checkout_only_once_the_clone_succeeded() {
+        let dir = tempdir::TempDir::new("git_remote").unwrap();
+        let remote = local_remote(dir.path());
+        let loc = dir.path().join("cache/dep");
+        fetch_git_repo(&remote, "v0.1.0", &loc).unwrap();
+        let first_commit = head_commit(&loc).unwrap();
+
+        // Move the tag to a new commit
+        let work = dir.path().join("work");
+        std::fs::write(work.join("src/lib.nr"), "fn bar() {}").unwrap();
+        git(&work, &["commit", "--quiet", "-am", "second commit"]);
+        git(&work, &["tag", "-f", "v0.1.0"]);
+        git(&work, &["push", "--quiet", "--force", "../remote.git", "refs/tags/v0.1.0"]);
+
+        // A failed update keeps the cached checkout
+        let unreachable = url::Url::from_file_path(dir.path().join("missing.git")).unwrap();
+        assert!(refetch_git_repo(&unreachable, "v0.1.0", &loc).is_err());
+        assert_eq!(head_commit(&loc).unwrap(), first_commit);
+        assert!(refetch_git_repo(&remote, "v9.9.9", &loc).is_err());
+        assert_eq!(head_commit(&loc).unwrap(), first_commit);
+
+        refetch_git_repo(&remote, "v0.1.0", &loc).unwrap();
+        assert_ne!(head_commit(&loc).unwrap(), first_commit);
+        assert_eq!(std::fs::read_to_string(loc.join("src/lib.nr")).unwrap(), "fn bar() {}");
+        assert!(!dir.path().join("cache/dep.partial").exists());
+        assert!(!dir.path().join("cache/dep.previous").exists());
+    }
 }
@@ -10,12 +10,18 @@ fn nargo_crates() -> PathBuf {
     dirs::home_dir().unwrap().join("nargo")
 }
 
+mod artifact;
 mod backends;
+mod build_cache;
 pub mod cli;
 mod errors;
 mod git;
+mod lockfile;
 mod resolver;
+mod stdlib;
 mod toml;
+mod vendor;
+mod workspace;
 
 /// Searches for the Nargo.toml file
 ///
@@ -0,0 +1,247 @@
+use serde_derive::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::errors::CliError;
+
+pub(crate) const LOCK_FILE: &str = "Nargo.lock";
+
+/// The version of the lockfile format, this must be bumped whenever its representation changes
+const LOCKFILE_VERSION: u32 = 1;
+
+const LOCKFILE_HEADER: &str =
+    "# This file is automatically generated by nargo, it is not meant to be edited by hand.\n\n";
+
+/// The git dependencies in a dependency graph, mapping the url and tag of each one
+/// to the directory it was checked out into
+pub(crate) type GitDependencies = BTreeMap<(String, String), PathBuf>;
+
+/// Records the commit which each git dependency in the dependency graph resolved to,
+/// along with a hash of its contents, so that every build uses exactly the same code.
+///
+/// Path dependencies are part of the project and so they are not locked.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Lockfile {
+    version: u32,
+    #[serde(default, rename = "package")]
+    packages: Vec<LockedPackage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct LockedPackage {
+    git: String,
+    tag: String,
+    commit: String,
+    checksum: String,
+}
+
+impl Lockfile {
+    /// Creates a lockfile from the current checkouts of the given git dependencies
+    pub(crate) fn from_git_deps(git_deps: &GitDependencies) -> Result<Lockfile, CliError> {
+        let packages = git_deps
+            .iter()
+            .map(|((git, tag), dir_path)| {
+                let commit = crate::git::head_commit(dir_path).map_err(CliError::Generic)?;
+                let checksum = checksum_dir(dir_path)?;
+                Ok(LockedPackage { git: git.clone(), tag: tag.clone(), commit, checksum })
+            })
+            .collect::<Result<_, CliError>>()?;
+        Ok(Lockfile { version: LOCKFILE_VERSION, packages })
+    }
+
+    /// Reads the lockfile of the package in `package_dir`, if it has one
+    pub(crate) fn read(package_dir: &Path) -> Result<Option<Lockfile>, CliError> {
+        let path = package_dir.join(LOCK_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = std::fs::read_to_string(&path).map_err(|err| {
+            CliError::Generic(format!("could not read {}: {}", path.display(), err))
+        })?;
+        let lockfile: Lockfile = toml::from_str(&contents).map_err(|err| {
+            CliError::Generic(format!(
+                "{} is badly formed, could not parse, {}",
+                path.display(),
+                err
+            ))
+        })?;
+        if lockfile.version != LOCKFILE_VERSION {
+            return Err(CliError::Generic(format!(
+                "unsupported lockfile version {} in {}, this version of nargo uses version {}",
+                lockfile.version,
+                path.display(),
+                LOCKFILE_VERSION
+            )));
+        }
+        Ok(Some(lockfile))
+    }
+
+    pub(crate) fn write(&self, package_dir: &Path) -> Result<(), CliError> {
+        let path = package_dir.join(LOCK_FILE);
+        let contents = toml::to_string(self).expect("ice: could not serialize the lockfile");
+        std::fs::write(&path, format!("{}{}", LOCKFILE_HEADER, contents)).map_err(|err| {
+            CliError::Generic(format!("could not write {}: {}", path.display(), err))
+        })
+    }
+
+    fn find(&self, git: &str, tag: &str) -> Option<&LockedPackage> {
+        self.packages.iter().find(|package| package.git == git && package.tag == tag)
+    }
+
+    /// Checks that every dependency which was resolved matches the locked one.
+    /// Dependencies which are not locked yet are accepted.
+    fn check(&self, resolved: &Lockfile) -> Result<(), CliError> {
+        for package in &resolved.packages {
+            let locked = match self.find(&package.git, &package.tag) {
+                Some(locked) => locked,
+                None => continue,
+            };
+            if locked.commit != package.commit {
+                return Err(CliError::Generic(format!(
+                    "the dependency {} (tag {}) resolved to commit {}, but {} locks it to commit {}. Run `nargo update` if this change is expected",
+                    package.git, package.tag, package.commit, LOCK_FILE, locked.commit
+                )));
+            }
+            if locked.checksum != package.checksum {
+                return Err(CliError::Generic(format!(
+                    "the contents of the dependency {} (tag {}) do not match the checksum in {}. Run `nargo update` if this change is expected",
+                    package.git, package.tag, LOCK_FILE
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Checks the git dependencies of the package in `package_dir` against its lockfile.
+///
+/// The lockfile is created if there is none, and it is updated when dependencies are
+/// added or removed, but a dependency which no longer matches its locked commit is an error.
+///
+/// With `keep_unused`, locked dependencies which were not resolved are kept in the lockfile,
+/// as they may be used by other members of a workspace.
+pub(crate) fn verify_lockfile(
+    package_dir: &Path,
+    git_deps: &GitDependencies,
+    keep_unused: bool,
+) -> Result<(), CliError> {
+    let locked = Lockfile::read(package_dir)?;
+    if locked.is_none() && git_deps.is_empty() {
+        return Ok(());
+    }
+
+    let mut resolved = Lockfile::from_git_deps(git_deps)?;
+    if let Some(locked) = &locked {
+        locked.check(&resolved)?;
+        if keep_unused {
+            let unused = locked
+                .packages
+                .iter()
+                .filter(|package| resolved.find(&package.git, &package.tag).is_none())
+                .cloned()
+                .collect::<Vec<_>>();
+            resolved.packages.extend(unused);
+            resolved.packages.sort_by(|a, b| (&a.git, &a.tag).cmp(&(&b.git, &b.tag)));
+        }
+    }
+    if locked.as_ref() != Some(&resolved) {
+        resolved.write(package_dir)?;
+    }
+    Ok(())
+}
+
+/// Hashes the contents of every file in a directory along with their paths,
+/// ignoring the git metadata
+pub(crate) fn checksum_dir(dir_path: &Path) -> Result<String, CliError> {
+    fn collect_files(
+        root: &Path,
+        dir: &Path,
+        files: &mut BTreeMap<String, PathBuf>,
+    ) -> std::io::Result<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.file_name().map_or(false, |name| name == ".git") {
+                continue;
+            }
+            if path.is_dir() {
+                collect_files(root, &path, files)?;
+            } else {
+                // Paths are hashed with `/` separators so that the checksum is the same on every platform
+                let relative_path = path.strip_prefix(root).expect("ice: path is in the directory");
+                let components: Vec<_> =
+                    relative_path.components().map(|c| c.as_os_str().to_string_lossy()).collect();
+                files.insert(components.join("/"), path);
+            }
+        }
+        Ok(())
+    }
+
+    let io_error = |err: std::io::Error| {
+        CliError::Generic(format!("could not read {}: {}", dir_path.display(), err))
+    };
+
+    let mut files = BTreeMap::new();
+    collect_files(dir_path, dir_path, &mut files).map_err(io_error)?;
+
+    let mut hasher = Sha256::new();
+    for (relative_path, path) in files {
+        let contents = std::fs::read(path).map_err(io_error)?;
+        hasher.update(relative_path.as_bytes());
+        hasher.update(&[0]);
+        hasher.update(&(contents.len() as u64).to_be_bytes());
+        hasher.update(&contents);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{checksum_dir, LockedPackage, Lockfile, LOCKFILE_VERSION};
+
+    fn lockfile(commit: &str, checksum: &str) -> Lockfile {
+        Lockfile {
+            version: LOCKFILE_VERSION,
+            packages: vec![LockedPackage {
+                git: "https://github.com/noir-lang/example".to_owned(),
+                tag: "v0.1.0".to_owned(),
+                commit: commit.to_owned(),
+                checksum: checksum.to_owned(),
+            }],
+        }
+    }
+
+    #[test]
+    fn detects_moved_tags() {
+        let locked = lockfile("abc", "123");
+        assert!(locked.check(&lockfile("abc", "123")).is_ok());
+        assert!(locked.check(&lockfile("def", "123")).is_err());
+        assert!(locked.check(&lockfile("abc", "456")).is_err());
+
+        let unlocked = Lockfile { version: LOCKFILE_VERSION, packages: Vec::new() };
+        assert!(unlocked.check(&lockfile("def", "456")).is_ok());
+    }
+
+    #[test]
+    fn serialization_round_trip() {
+        let locked = lockfile("abc", "123");
+        let serialized = toml::to_string(&locked).unwrap();
+        assert_eq!(toml::from_str::<Lockfile>(&serialized).unwrap(), locked);
+    }
+
+    #[test]
+    fn checksum_depends_on_contents() {
+        let dir = tempdir::TempDir::new("checksum").unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/lib.nr"), "fn foo() {}").unwrap();
+        let checksum = checksum_dir(dir.path()).unwrap();
+
+        // The git metadata is ignored
+        std::fs::create_dir(dir.path().join(".git")).unwrap();
+        std::fs::write(dir.path().join(".git/HEAD"), "ref: refs/heads/master").unwrap();
+        assert_eq!(checksum_dir(dir.path()).unwrap(), checksum);
+
+        std::fs::write(dir.path().join("src/lib.nr"), "fn bar() {}").unwrap();
+        assert_ne!(checksum_dir(dir.path()).unwrap(), checksum);
+    }
+}
@@ -1,5 +1,5 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, BTreeSet, HashMap},
     path::{Path, PathBuf},
 };
 
@@ -8,13 +8,17 @@ use noirc_frontend::graph::{CrateId, CrateType};
 
 use crate::{
     errors::CliError,
+    lockfile::{verify_lockfile, GitDependencies, Lockfile},
     toml::{Config, Dependency},
+    vendor::{VendorManifest, VENDOR_DIR},
+    workspace::{dependency_root, normalize_path},
 };
 
 /// Creates a unique folder name for a GitHub repo
 /// by using it's URL and tag
 pub(crate) fn resolve_folder_name(base: &url::Url, tag: &str) -> String {
-    let mut folder_name = base.domain().unwrap().to_owned();
+    // Repositories on the local file system, such as `file:///path/to/repo`, have no domain
+    let mut folder_name = base.domain().unwrap_or("local").to_owned();
     folder_name.push_str(base.path());
     folder_name.push_str(tag);
     folder_name
@@ -28,6 +32,37 @@ struct CachedDep {
     // Whether the dependency came from
     // a remote dependency
     remote: bool,
+    // The path of the package relative to the root of the dependency graph,
+    // which the path dependencies of the package are relative to
+    source_path: PathBuf,
+}
+
+/// A dependency in the dependency graph, with the name it was first declared under
+/// and the directory its source was found in
+#[derive(Debug, Clone)]
+pub(crate) struct ResolvedPackage {
+    pub(crate) name: String,
+    pub(crate) dir_path: PathBuf,
+}
+
+/// Every dependency in the dependency graph, by its source.
+///
+/// Path dependencies are relative to the root of the dependency graph, so that the
+/// same dependency has the same source wherever it is depended upon.
+pub(crate) type ResolvedPackages = BTreeMap<Dependency, ResolvedPackage>;
+
+/// What is found while resolving the dependency graph,
+/// shared by the resolvers of every package in the graph
+#[derive(Default)]
+struct DependencyGraph {
+    // The package, or the root of its workspace, which dependencies are locked and vendored in
+    root_dir: PathBuf,
+    // The vendored copies of the dependencies which are used instead of their sources
+    vendored: Option<VendorManifest>,
+    packages: ResolvedPackages,
+    git_deps: GitDependencies,
+    // The git dependencies which are not in the cache, when nargo is offline
+    missing_offline: BTreeSet<String>,
 }
 
 /// Resolves a toml file by either downloading the necessary git repo
@@ -37,19 +72,99 @@ struct CachedDep {
 pub struct Resolver<'a> {
     cached_packages: HashMap<PathBuf, (CrateId, CachedDep)>,
     driver: &'a mut Driver,
+    graph: &'a mut DependencyGraph,
 }
 
 impl<'a> Resolver<'a> {
-    fn with_driver(driver: &mut Driver) -> Resolver {
-        Resolver { cached_packages: HashMap::new(), driver }
+    fn with_driver(driver: &'a mut Driver, graph: &'a mut DependencyGraph) -> Resolver<'a> {
+        Resolver { cached_packages: HashMap::new(), driver, graph }
     }
 
     /// Returns the Driver and the backend to use
     /// Note that the backend is ignored in the dependencies.
     /// Since Noir is backend agnostic, this is okay to do.
+    ///
+    /// Vendored dependencies are used when the package has any, otherwise
+    /// the git dependencies are checked against the Nargo.lock of the package.
+    /// The members of a workspace share the vendor directory and Nargo.lock in its root.
     /// XXX: Need to handle when a local package changes!
     pub fn resolve_root_config(dir_path: &std::path::Path) -> Result<Driver, CliError> {
+        let (root_dir, package_path, workspace) = dependency_root(dir_path)?;
+        let vendored = VendorManifest::read(&root_dir)?;
+        let is_vendored = vendored.is_some();
+        let (driver, graph) =
+            Resolver::resolve_dependency_graph(dir_path, root_dir, package_path, vendored)?;
+        if !is_vendored {
+            // A member does not use the dependencies of the other members, so they are kept
+            let keep_unused = workspace.is_some();
+            verify_lockfile(&graph.root_dir, &graph.git_deps, keep_unused)?;
+        }
+        Ok(driver)
+    }
+
+    /// Resolves every dependency from its source, ignoring any vendored copies.
+    ///
+    /// For a workspace member, the dependencies of every member are resolved.
+    /// Returns them along with the directory they are vendored into.
+    pub(crate) fn resolve_sources(
+        dir_path: &std::path::Path,
+    ) -> Result<(PathBuf, ResolvedPackages), CliError> {
+        let graph = Resolver::resolve_shared_graph(dir_path)?;
+        Ok((graph.root_dir, graph.packages))
+    }
+
+    /// Fetches each git dependency of the package again, so that tags which were moved
+    /// resolve to their new commit, then rewrites the Nargo.lock of the package.
+    pub fn update_lockfile(dir_path: &std::path::Path) -> Result<(), CliError> {
+        if super::git::is_offline() {
+            return Err(CliError::Generic(
+                "git dependencies cannot be updated while offline".to_owned(),
+            ));
+        }
+        let graph = Resolver::resolve_shared_graph(dir_path)?;
+        for (url, tag) in graph.git_deps.keys() {
+            super::git::update_git_repo(url, tag)?;
+        }
+
+        let graph = Resolver::resolve_shared_graph(dir_path)?;
+        Lockfile::from_git_deps(&graph.git_deps)?.write(&graph.root_dir)
+    }
+
+    /// Resolves the dependencies of every package which shares its dependencies with the
+    /// package in `dir_path`, these are the members of its workspace or the package alone
+    fn resolve_shared_graph(dir_path: &std::path::Path) -> Result<DependencyGraph, CliError> {
+        let (root_dir, package_path, workspace) = dependency_root(dir_path)?;
+        let packages = match &workspace {
+            Some(workspace) => workspace.member_paths(),
+            None => vec![package_path],
+        };
+
+        let mut shared =
+            DependencyGraph { root_dir: root_dir.clone(), ..DependencyGraph::default() };
+        for package_path in packages {
+            let package_dir = root_dir.join(&package_path);
+            let (_, graph) = Resolver::resolve_dependency_graph(
+                &package_dir,
+                root_dir.clone(),
+                package_path,
+                None,
+            )?;
+            for (source, package) in graph.packages {
+                shared.packages.entry(source).or_insert(package);
+            }
+            shared.git_deps.extend(graph.git_deps);
+        }
+        Ok(shared)
+    }
+
+    fn resolve_dependency_graph(
+        dir_path: &std::path::Path,
+        root_dir: PathBuf,
+        package_path: PathBuf,
+        vendored: Option<VendorManifest>,
+    ) -> Result<(Driver, DependencyGraph), CliError> {
         let mut driver = Driver::new();
+        let mut graph = DependencyGraph { root_dir, vendored, ..DependencyGraph::default() };
 
         let (entry_path, crate_type) = super::lib_or_bin(dir_path)?;
 
@@ -58,10 +173,15 @@ impl<'a> Resolver<'a> {
 
         let crate_id = driver.create_local_crate(entry_path, crate_type);
 
-        let mut resolver = Resolver::with_driver(&mut driver);
-        resolver.resolve_config(crate_id, cfg)?;
+        let mut resolver = Resolver::with_driver(&mut driver, &mut graph);
+        resolver.resolve_config(crate_id, cfg, &package_path)?;
 
-        Ok(driver)
+        if !graph.missing_offline.is_empty() {
+            let missing = graph.missing_offline.into_iter().collect();
+            return Err(CliError::MissingOfflineDependencies(missing));
+        }
+
+        Ok((driver, graph))
     }
 
     // Resolves a config file by recursively resolving the dependencies in the config
@@ -70,10 +190,37 @@ impl<'a> Resolver<'a> {
     // We do not need to add stdlib, as it's implicitly
     // imported. However, it may be helpful to have the stdlib imported by the
     // package manager.
-    fn resolve_config(&mut self, parent_crate: CrateId, cfg: Config) -> Result<(), CliError> {
+    fn resolve_config(
+        &mut self,
+        parent_crate: CrateId,
+        cfg: Config,
+        source_path: &Path,
+    ) -> Result<(), CliError> {
         // First download and add these top level dependencies crates to the Driver
         for (dep_pkg_name, pkg_src) in cfg.dependencies.iter() {
-            let (dir_path, dep_meta) = Resolver::cache_dep(pkg_src)?;
+            let pkg_src = match pkg_src {
+                Dependency::Path { path } => {
+                    let path = normalize_path(&source_path.join(path));
+                    Dependency::Path { path: path.to_string_lossy().into_owned() }
+                }
+                Dependency::Github { .. } => pkg_src.clone(),
+            };
+            let (dir_path, dep_meta) = match self.cache_dep(dep_pkg_name, &pkg_src) {
+                Ok(dep) => dep,
+                // Keep resolving the other dependencies, so that all of the missing ones are listed
+                Err(CliError::MissingOfflineDependencies(deps)) => {
+                    self.graph.missing_offline.extend(deps);
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
+            if let (Dependency::Github { git, tag }, None) = (&pkg_src, &self.graph.vendored) {
+                self.graph.git_deps.insert((git.clone(), tag.clone()), dir_path.clone());
+            }
+            self.graph.packages.entry(pkg_src.clone()).or_insert_with(|| ResolvedPackage {
+                name: dep_pkg_name.clone(),
+                dir_path: dir_path.clone(),
+            });
 
             let (entry_path, crate_type) = (&dep_meta.entry_path, &dep_meta.crate_type);
 
@@ -98,8 +245,8 @@ impl<'a> Resolver<'a> {
                     dir_path.display()
                 )));
             }
-            let mut new_res = Resolver::with_driver(self.driver);
-            new_res.resolve_config(*crate_id, dep_meta.cfg.clone())?;
+            let mut new_res = Resolver::with_driver(self.driver, self.graph);
+            new_res.resolve_config(*crate_id, dep_meta.cfg.clone(), &dep_meta.source_path)?;
         }
         Ok(())
     }
@@ -110,32 +257,65 @@ impl<'a> Resolver<'a> {
     ///
     /// If it's a local path, the same applies, however it will not
     /// be downloaded
-    fn cache_dep(dep: &Dependency) -> Result<(PathBuf, CachedDep), CliError> {
-        fn retrieve_meta(dir_path: &Path, remote: bool) -> Result<CachedDep, CliError> {
+    ///
+    /// When the dependencies are vendored, the vendored copy is used instead
+    ///
+    /// Path dependencies must be relative to the root of the dependency graph
+    fn cache_dep(&self, name: &str, dep: &Dependency) -> Result<(PathBuf, CachedDep), CliError> {
+        fn retrieve_meta(
+            dir_path: &Path,
+            remote: bool,
+            source_path: PathBuf,
+        ) -> Result<CachedDep, CliError> {
             let (entry_path, crate_type) = super::lib_or_bin(dir_path)?;
             let cfg_path = super::find_package_config(dir_path)?;
             let cfg = super::toml::parse(cfg_path)?;
-            Ok(CachedDep { entry_path, crate_type, cfg, remote })
+            Ok(CachedDep { entry_path, crate_type, cfg, remote, source_path })
+        }
+
+        // The vendored copy of a path dependency keeps the path of its source,
+        // so that its own path dependencies resolve to their vendored copies
+        let source_path = |dir_path: &Path| match dep {
+            Dependency::Path { path } => PathBuf::from(path),
+            Dependency::Github { .. } => dir_path.to_path_buf(),
+        };
+
+        if let Some(manifest) = &self.graph.vendored {
+            let vendor_dir = self.graph.root_dir.join(VENDOR_DIR);
+            let dir_path = match manifest.vendored_dir(dep) {
+                Some(directory) => vendor_dir.join(directory),
+                None => {
+                    return Err(CliError::Generic(format!(
+                        "the dependency {} is not vendored, run `nargo vendor` to copy it into {}",
+                        name,
+                        vendor_dir.display()
+                    )))
+                }
+            };
+            let remote = matches!(dep, Dependency::Github { .. });
+            let meta = retrieve_meta(&dir_path, remote, source_path(&dir_path))?;
+            return Ok((dir_path, meta));
         }
 
         match dep {
             Dependency::Github { git, tag } => {
                 let dir_path = Resolver::resolve_git_dep(git, tag)?;
-                let meta = retrieve_meta(&dir_path, true)?;
+                let meta = retrieve_meta(&dir_path, true, source_path(&dir_path))?;
                 Ok((dir_path, meta))
             }
             Dependency::Path { path } => {
-                let dir_path = std::path::PathBuf::from(path);
-                let meta = retrieve_meta(&dir_path, false)?;
+                let dir_path = self.graph.root_dir.join(path);
+                let meta = retrieve_meta(&dir_path, false, source_path(&dir_path))?;
                 Ok((dir_path, meta))
             }
         }
     }
 
     pub fn resolve_git_dep(url: &str, tag: &str) -> Result<PathBuf, CliError> {
-        match super::git::clone_git_repo(url, tag) {
-            Ok(path) => Ok(path),
-            Err(msg) => Err(CliError::Generic(msg)),
+        if super::git::is_offline() {
+            super::git::cached_git_repo(url, tag)
+        } else {
+            super::git::clone_git_repo(url, tag)
         }
     }
 }
@@ -0,0 +1,56 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use noirc_driver::Driver;
+
+use crate::errors::CliError;
+
+/// The sources of the standard library, by their path relative to its `src` directory.
+/// These are embedded by the build script.
+const STDLIB_FILES: &[(&str, &str)] = include!(concat!(env!("OUT_DIR"), "/stdlib.rs"));
+
+/// The directory which the embedded standard library appears to be in.
+/// Nothing is read from this directory, it only names the files in diagnostics.
+const EMBEDDED_STDLIB_DIR: &str = "<std>";
+
+/// A standard library on the file system which is used instead of the embedded one
+static STDLIB_OVERRIDE: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Uses the standard library in the package at `stdlib_dir` instead of the embedded one,
+/// this is useful when developing the standard library
+pub(crate) fn set_stdlib_override(stdlib_dir: &Path) -> Result<(), CliError> {
+    if !stdlib_dir.join("src").join("lib.nr").exists() {
+        return Err(CliError::Generic(format!(
+            "cannot find the standard library in {}, it must contain a src/lib.nr",
+            stdlib_dir.display()
+        )));
+    }
+    *STDLIB_OVERRIDE.lock().unwrap() = Some(stdlib_dir.to_path_buf());
+    Ok(())
+}
+
+/// Makes the standard library available to the driver, returning the path of its root file
+pub(crate) fn load_stdlib(driver: &mut Driver) -> PathBuf {
+    if let Some(stdlib_dir) = STDLIB_OVERRIDE.lock().unwrap().as_ref() {
+        return stdlib_dir.join("src").join("lib.nr");
+    }
+
+    let embedded_dir = Path::new(EMBEDDED_STDLIB_DIR);
+    for (relative_path, source) in STDLIB_FILES {
+        driver.add_embedded_file(embedded_dir.join(relative_path), source.to_string());
+    }
+    embedded_dir.join("lib.nr")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::STDLIB_FILES;
+
+    #[test]
+    fn embeds_the_stdlib() {
+        let lib = STDLIB_FILES.iter().find(|(path, _)| *path == "lib.nr");
+        assert!(lib.is_some());
+        let expected = include_str!("../../../noir_stdlib/src/lib.nr");
+        assert_eq!(lib.unwrap().1, expected);
+    }
+}
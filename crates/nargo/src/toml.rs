@@ -39,7 +39,7 @@ pub struct Package {
     pub license: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(untagged)]
 /// Enum representing the different types of ways to
 /// supply a source for the dependency
@@ -48,6 +48,35 @@ pub enum Dependency {
     Path { path: String },
 }
 
+#[derive(Debug, Deserialize, Clone)]
+pub struct WorkspaceConfig {
+    // The directories of the packages in the workspace, relative to the Nargo.toml
+    pub members: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WorkspaceManifest {
+    workspace: Option<WorkspaceConfig>,
+}
+
+/// Parses the `[workspace]` section of a Nargo.toml file, if it has one.
+/// A workspace does not need to be a package itself, so the rest of the file is ignored.
+pub fn parse_workspace<P: AsRef<Path>>(
+    path_to_toml: P,
+) -> Result<Option<WorkspaceConfig>, CliError> {
+    let toml_as_string =
+        std::fs::read_to_string(&path_to_toml).expect("ice: path given for toml file is invalid");
+
+    match toml::from_str::<WorkspaceManifest>(&toml_as_string) {
+        Ok(manifest) => Ok(manifest.workspace),
+        Err(err) => Err(CliError::Generic(format!(
+            "the workspace is badly formed, could not parse\n\n{}\n Location: {}",
+            err,
+            path_to_toml.as_ref().display()
+        ))),
+    }
+}
+
 /// Parses a Nargo.toml file from it's path
 /// The path to the toml file must be present.
 /// Calling this function without this guarantee is an ICE.
@@ -95,3 +124,14 @@ fn parse_standard_toml() {
 
     assert!(parse_toml_str(src).is_ok());
 }
+
+#[test]
+fn parse_workspace_toml() {
+    let src = r#"
+        [workspace]
+        members = ["circuits/a", "libs/b"]
+    "#;
+
+    let manifest = toml::from_str::<WorkspaceManifest>(src).unwrap();
+    assert_eq!(manifest.workspace.unwrap().members, vec!["circuits/a", "libs/b"]);
+}
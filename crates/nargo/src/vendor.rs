@@ -0,0 +1,326 @@
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use crate::errors::CliError;
+use crate::lockfile::checksum_dir;
+use crate::resolver::ResolvedPackages;
+use crate::toml::Dependency;
+
+pub(crate) const VENDOR_DIR: &str = "vendor";
+
+/// Records where each dependency was vendored from, this is kept in the vendor directory
+const VENDOR_MANIFEST: &str = "vendor.toml";
+
+/// The version of the vendor manifest format, this must be bumped whenever its representation changes
+const VENDOR_MANIFEST_VERSION: u32 = 1;
+
+const VENDOR_MANIFEST_HEADER: &str =
+    "# This file is automatically generated by `nargo vendor`, it is not meant to be edited by hand.\n\n";
+
+/// The dependencies which were copied into the vendor directory of a package.
+///
+/// When a package has a vendor manifest, its dependencies are resolved to the
+/// vendored copies instead of being downloaded into ~/nargo.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct VendorManifest {
+    version: u32,
+    #[serde(default, rename = "package")]
+    packages: Vec<VendoredPackage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct VendoredPackage {
+    name: String,
+    // The directory of the copy, relative to the vendor directory
+    directory: String,
+    git: Option<String>,
+    tag: Option<String>,
+    // The commit the git dependency was checked out at when it was vendored
+    commit: Option<String>,
+    path: Option<String>,
+    checksum: String,
+}
+
+impl VendoredPackage {
+    fn source(&self) -> Option<Dependency> {
+        match (&self.git, &self.tag, &self.path) {
+            (Some(git), Some(tag), None) => {
+                Some(Dependency::Github { git: git.clone(), tag: tag.clone() })
+            }
+            (None, None, Some(path)) => Some(Dependency::Path { path: path.clone() }),
+            _ => None,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match (&self.git, &self.tag, &self.path) {
+            (Some(git), Some(tag), _) => format!("{} ({} tag {})", self.name, git, tag),
+            (_, _, Some(path)) => format!("{} (path {})", self.name, path),
+            _ => self.name.clone(),
+        }
+    }
+}
+
+impl VendorManifest {
+    /// Reads the vendor manifest of the package in `package_dir`, if its dependencies are vendored
+    pub(crate) fn read(package_dir: &Path) -> Result<Option<VendorManifest>, CliError> {
+        let path = package_dir.join(VENDOR_DIR).join(VENDOR_MANIFEST);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = std::fs::read_to_string(&path).map_err(|err| {
+            CliError::Generic(format!("could not read {}: {}", path.display(), err))
+        })?;
+        let manifest: VendorManifest = toml::from_str(&contents).map_err(|err| {
+            CliError::Generic(format!(
+                "{} is badly formed, could not parse, {}",
+                path.display(),
+                err
+            ))
+        })?;
+        if manifest.version != VENDOR_MANIFEST_VERSION {
+            return Err(CliError::Generic(format!(
+                "unsupported vendor manifest version {} in {}, this version of nargo uses version {}",
+                manifest.version,
+                path.display(),
+                VENDOR_MANIFEST_VERSION
+            )));
+        }
+        if let Some(package) = manifest.packages.iter().find(|package| package.source().is_none()) {
+            return Err(CliError::Generic(format!(
+                "the vendored dependency {} in {} must have either a git url and tag or a path",
+                package.name,
+                path.display()
+            )));
+        }
+        Ok(Some(manifest))
+    }
+
+    fn write(&self, package_dir: &Path) -> Result<(), CliError> {
+        let path = package_dir.join(VENDOR_DIR).join(VENDOR_MANIFEST);
+        let contents = toml::to_string(self).expect("ice: could not serialize the vendor manifest");
+        std::fs::write(&path, format!("{}{}", VENDOR_MANIFEST_HEADER, contents)).map_err(|err| {
+            CliError::Generic(format!("could not write {}: {}", path.display(), err))
+        })
+    }
+
+    /// Returns the directory the dependency was vendored into, relative to the vendor directory
+    pub(crate) fn vendored_dir(&self, dep: &Dependency) -> Option<&str> {
+        self.packages
+            .iter()
+            .find(|package| package.source().as_ref() == Some(dep))
+            .map(|package| package.directory.as_str())
+    }
+
+    /// Describes the dependencies as they would be vendored, without copying them
+    fn from_sources(packages: &ResolvedPackages) -> Result<VendorManifest, CliError> {
+        let mut directories = BTreeSet::new();
+        let mut vendored = Vec::with_capacity(packages.len());
+        for (source, package) in packages {
+            let (git, tag, commit, path, directory) = match source {
+                Dependency::Github { git, tag } => {
+                    let commit =
+                        crate::git::head_commit(&package.dir_path).map_err(CliError::Generic)?;
+                    let directory = format!("{}-{}", package.name, tag.replace('/', "_"));
+                    (Some(git.clone()), Some(tag.clone()), Some(commit), None, directory)
+                }
+                Dependency::Path { path } => {
+                    (None, None, None, Some(path.clone()), package.name.clone())
+                }
+            };
+
+            // The same package may be depended upon from different sources
+            let mut unique_directory = directory.clone();
+            let mut suffix = 1;
+            while !directories.insert(unique_directory.clone()) {
+                suffix += 1;
+                unique_directory = format!("{}-{}", directory, suffix);
+            }
+
+            vendored.push(VendoredPackage {
+                name: package.name.clone(),
+                directory: unique_directory,
+                git,
+                tag,
+                commit,
+                path,
+                checksum: checksum_dir(&package.dir_path)?,
+            });
+        }
+        Ok(VendorManifest { version: VENDOR_MANIFEST_VERSION, packages: vendored })
+    }
+}
+
+/// Copies every dependency into the vendor directory of the package in `package_dir`,
+/// replacing any dependencies which were vendored before.
+///
+/// Returns the number of dependencies which were vendored.
+pub(crate) fn vendor_packages(
+    package_dir: &Path,
+    packages: &ResolvedPackages,
+) -> Result<usize, CliError> {
+    let manifest = VendorManifest::from_sources(packages)?;
+
+    let vendor_dir = package_dir.join(VENDOR_DIR);
+    if vendor_dir.exists() {
+        std::fs::remove_dir_all(&vendor_dir).map_err(|err| {
+            CliError::Generic(format!("could not remove {}: {}", vendor_dir.display(), err))
+        })?;
+    }
+    std::fs::create_dir_all(&vendor_dir).map_err(|err| {
+        CliError::Generic(format!("could not create {}: {}", vendor_dir.display(), err))
+    })?;
+
+    for vendored in &manifest.packages {
+        let source = vendored.source().expect("ice: vendored packages are created with a source");
+        let from = &packages[&source].dir_path;
+        copy_dir(from, &vendor_dir.join(&vendored.directory)).map_err(|err| {
+            CliError::Generic(format!("could not vendor {}: {}", from.display(), err))
+        })?;
+    }
+
+    manifest.write(package_dir)?;
+    Ok(manifest.packages.len())
+}
+
+/// Checks that the vendored dependencies of the package in `package_dir` match their sources,
+/// and that they were not modified since they were vendored.
+pub(crate) fn check_vendored_packages(
+    package_dir: &Path,
+    packages: &ResolvedPackages,
+) -> Result<(), CliError> {
+    let vendor_dir = package_dir.join(VENDOR_DIR);
+    let vendored = match VendorManifest::read(package_dir)? {
+        Some(vendored) => vendored,
+        None => {
+            return Err(CliError::Generic(format!(
+                "there are no vendored dependencies in {}, run `nargo vendor` first",
+                vendor_dir.display()
+            )))
+        }
+    };
+    let expected = VendorManifest::from_sources(packages)?;
+
+    let mut drift = Vec::new();
+    for package in &vendored.packages {
+        let source = package.source().expect("ice: the vendor manifest was validated");
+        match expected.packages.iter().find(|expected| expected.source() == Some(source.clone())) {
+            None => drift.push(format!("{} is no longer a dependency", package.describe())),
+            Some(expected) if expected.commit != package.commit => drift.push(format!(
+                "{} was vendored at commit {}, but its tag now resolves to commit {}",
+                package.describe(),
+                package.commit.as_deref().unwrap_or_default(),
+                expected.commit.as_deref().unwrap_or_default()
+            )),
+            Some(expected) if expected.checksum != package.checksum => {
+                drift.push(format!("{} has changed since it was vendored", package.describe()))
+            }
+            Some(_) => (),
+        }
+
+        let copy = vendor_dir.join(&package.directory);
+        if !copy.exists() || checksum_dir(&copy)? != package.checksum {
+            drift.push(format!(
+                "the vendored copy of {} in {} was modified",
+                package.describe(),
+                copy.display()
+            ));
+        }
+    }
+    for package in &expected.packages {
+        let source = package.source().expect("ice: vendored packages are created with a source");
+        if vendored.vendored_dir(&source).is_none() {
+            drift.push(format!("{} is not vendored", package.describe()));
+        }
+    }
+
+    if drift.is_empty() {
+        return Ok(());
+    }
+    Err(CliError::Generic(format!(
+        "the vendored dependencies do not match their sources:\n    {}\nrun `nargo vendor` to vendor them again",
+        drift.join("\n    ")
+    )))
+}
+
+/// Recursively copies a directory, leaving out the git metadata
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let path = entry?.path();
+        let file_name = path.file_name().expect("ice: directory entries have a name");
+        if file_name == ".git" {
+            continue;
+        }
+        let destination: PathBuf = to.join(file_name);
+        if path.is_dir() {
+            copy_dir(&path, &destination)?;
+        } else {
+            std::fs::copy(&path, &destination)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{check_vendored_packages, vendor_packages, VendorManifest, VENDOR_DIR};
+    use crate::resolver::{ResolvedPackage, ResolvedPackages};
+    use crate::toml::Dependency;
+
+    fn library(dir: &Path, contents: &str) {
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::write(dir.join("Nargo.toml"), "[package]\nauthors = []\n\n[dependencies]\n")
+            .unwrap();
+        std::fs::write(dir.join("src/lib.nr"), contents).unwrap();
+    }
+
+    fn path_dependency(dir: &Path, name: &str) -> ResolvedPackages {
+        let source = Dependency::Path { path: dir.display().to_string() };
+        ResolvedPackages::from([(
+            source,
+            ResolvedPackage { name: name.to_owned(), dir_path: dir.to_path_buf() },
+        )])
+    }
+
+    #[test]
+    fn vendored_dependencies_are_resolved() {
+        let dir = tempdir::TempDir::new("vendor").unwrap();
+        let dep_dir = dir.path().join("dep");
+        library(&dep_dir, "fn foo() {}");
+        let package_dir = dir.path().join("package");
+        std::fs::create_dir_all(&package_dir).unwrap();
+
+        let packages = path_dependency(&dep_dir, "dep");
+        assert_eq!(vendor_packages(&package_dir, &packages).unwrap(), 1);
+        assert!(package_dir.join(VENDOR_DIR).join("dep/src/lib.nr").exists());
+
+        let manifest = VendorManifest::read(&package_dir).unwrap().unwrap();
+        let (source, _) = packages.iter().next().unwrap();
+        assert_eq!(manifest.vendored_dir(source), Some("dep"));
+        assert!(check_vendored_packages(&package_dir, &packages).is_ok());
+    }
+
+    #[test]
+    fn check_detects_drift() {
+        let dir = tempdir::TempDir::new("vendor").unwrap();
+        let dep_dir = dir.path().join("dep");
+        library(&dep_dir, "fn foo() {}");
+        let package_dir = dir.path().join("package");
+        std::fs::create_dir_all(&package_dir).unwrap();
+        let packages = path_dependency(&dep_dir, "dep");
+        vendor_packages(&package_dir, &packages).unwrap();
+
+        // The source changed since it was vendored
+        library(&dep_dir, "fn bar() {}");
+        assert!(check_vendored_packages(&package_dir, &packages).is_err());
+
+        // The vendored copy was edited
+        vendor_packages(&package_dir, &packages).unwrap();
+        std::fs::write(package_dir.join(VENDOR_DIR).join("dep/src/lib.nr"), "fn baz() {}").unwrap();
+        assert!(check_vendored_packages(&package_dir, &packages).is_err());
+    }
+}
@@ -1,4 +1,302 @@
 // We will say that a cargo unit must contain either a binary or a library
-// Then we use workspace to allow more than one. In the future, do not allow there to be 
+// Then we use workspace to allow more than one. In the future, do not allow there to be
 // both a binary and a library.
 // - library will be default
+//
+// A workspace is a Nargo.toml with a `[workspace]` section listing the directories of its
+// members, which are ordinary packages. The members share a Nargo.lock, a vendor directory
+// and the directories that nargo writes its outputs into, which are all kept in the root
+// of the workspace.
+
+use std::path::{Component, Path, PathBuf};
+
+use crate::errors::CliError;
+
+#[derive(Debug, Clone)]
+pub(crate) struct Workspace {
+    pub(crate) root_dir: PathBuf,
+    // The directories of the members, relative to the root of the workspace
+    members: Vec<PathBuf>,
+}
+
+/// A package which a command operates on
+#[derive(Debug, Clone)]
+pub(crate) struct SelectedPackage {
+    pub(crate) name: String,
+    pub(crate) dir: PathBuf,
+    // The root of the workspace the package is a member of, if any
+    workspace_root: Option<PathBuf>,
+}
+
+impl SelectedPackage {
+    /// Returns the directory which outputs of the given kind, such as proofs, are written into.
+    ///
+    /// The members of a workspace share these directories, so each one writes into its own
+    /// subdirectory of the directory in the root of the workspace.
+    pub(crate) fn output_dir(&self, dir_name: &str) -> PathBuf {
+        match &self.workspace_root {
+            Some(root_dir) => root_dir.join(dir_name).join(&self.name),
+            None => PathBuf::from(dir_name),
+        }
+    }
+}
+
+impl Workspace {
+    /// Searches `dir` and its ancestors for a workspace which has `dir` as its root or as one of its members
+    pub(crate) fn find(dir: &Path) -> Result<Option<Workspace>, CliError> {
+        let dir = canonicalize(dir)?;
+        for candidate in dir.ancestors() {
+            let cfg_path = candidate.join(crate::cli::PKG_FILE);
+            if !cfg_path.exists() {
+                continue;
+            }
+            let members = match crate::toml::parse_workspace(&cfg_path)? {
+                Some(workspace) => workspace.members,
+                None => continue,
+            };
+            let workspace = Workspace::new(candidate, members, &cfg_path)?;
+            if candidate == dir || workspace.member_path(&dir).is_some() {
+                return Ok(Some(workspace));
+            }
+        }
+        Ok(None)
+    }
+
+    fn new(root_dir: &Path, members: Vec<String>, cfg_path: &Path) -> Result<Workspace, CliError> {
+        let workspace = Workspace {
+            root_dir: root_dir.to_path_buf(),
+            members: members.iter().map(|member| normalize_path(Path::new(member))).collect(),
+        };
+
+        let mut names = Vec::with_capacity(workspace.members.len());
+        for member in &workspace.members {
+            let name = member_name(member).ok_or_else(|| {
+                CliError::Generic(format!(
+                    "the workspace member {} is not a directory\n Location: {}",
+                    member.display(),
+                    cfg_path.display()
+                ))
+            })?;
+            if !workspace.root_dir.join(member).join(crate::cli::PKG_FILE).exists() {
+                return Err(CliError::Generic(format!(
+                    "cannot find a Nargo.toml for the workspace member {}\n Location: {}",
+                    member.display(),
+                    cfg_path.display()
+                )));
+            }
+            if names.contains(&name) {
+                return Err(CliError::Generic(format!(
+                    "there is more than one workspace member named {}\n Location: {}",
+                    name,
+                    cfg_path.display()
+                )));
+            }
+            names.push(name);
+        }
+        Ok(workspace)
+    }
+
+    /// Returns the path of the member in `dir` relative to the root of the workspace,
+    /// if `dir` is a member of the workspace
+    pub(crate) fn member_path(&self, dir: &Path) -> Option<PathBuf> {
+        let dir = canonicalize(dir).ok()?;
+        self.members
+            .iter()
+            .find(|member| canonicalize(&self.root_dir.join(member)).ok() == Some(dir.clone()))
+            .cloned()
+    }
+
+    /// Returns the directories of every member, relative to the root of the workspace
+    pub(crate) fn member_paths(&self) -> Vec<PathBuf> {
+        self.members.clone()
+    }
+
+    fn selected(&self, member: &Path) -> SelectedPackage {
+        SelectedPackage {
+            name: member_name(member).expect("ice: members were checked to be directories"),
+            dir: self.root_dir.join(member),
+            workspace_root: Some(self.root_dir.clone()),
+        }
+    }
+}
+
+/// Chooses the packages a command operates on, from the directory it was run in.
+///
+/// In a workspace, `package` selects a member by name. Otherwise the member which
+/// the command was run in is selected, or every member if it was run in the root.
+/// Outside of a workspace, the package in `current_dir` is selected.
+pub(crate) fn select_packages(
+    current_dir: &Path,
+    package: Option<&str>,
+) -> Result<Vec<SelectedPackage>, CliError> {
+    let workspace = match Workspace::find(current_dir)? {
+        Some(workspace) => workspace,
+        None => {
+            if let Some(package) = package {
+                return Err(CliError::Generic(format!(
+                    "cannot select the package {} as {} is not in a workspace",
+                    package,
+                    current_dir.display()
+                )));
+            }
+            let name = current_dir
+                .file_name()
+                .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+            return Ok(vec![SelectedPackage {
+                name,
+                dir: current_dir.to_path_buf(),
+                workspace_root: None,
+            }]);
+        }
+    };
+
+    if let Some(package) = package {
+        return match workspace
+            .members
+            .iter()
+            .find(|member| member_name(member).as_deref() == Some(package))
+        {
+            Some(member) => Ok(vec![workspace.selected(member)]),
+            None => {
+                let names: Vec<_> =
+                    workspace.members.iter().filter_map(|member| member_name(member)).collect();
+                Err(CliError::Generic(format!(
+                    "the workspace in {} does not have a member named {}, its members are: {}",
+                    workspace.root_dir.display(),
+                    package,
+                    names.join(", ")
+                )))
+            }
+        };
+    }
+
+    match workspace.member_path(current_dir) {
+        Some(member) => Ok(vec![workspace.selected(&member)]),
+        None => Ok(workspace.members.iter().map(|member| workspace.selected(member)).collect()),
+    }
+}
+
+/// Returns the directory which the dependencies of the package in `package_dir` are locked and
+/// vendored in, along with the path of the package relative to it.
+///
+/// This is the root of the workspace for a workspace member, and the package itself otherwise.
+pub(crate) fn dependency_root(
+    package_dir: &Path,
+) -> Result<(PathBuf, PathBuf, Option<Workspace>), CliError> {
+    match Workspace::find(package_dir)? {
+        Some(workspace) => {
+            let member = workspace.member_path(package_dir).unwrap_or_default();
+            Ok((workspace.root_dir.clone(), member, Some(workspace)))
+        }
+        None => Ok((package_dir.to_path_buf(), PathBuf::new(), None)),
+    }
+}
+
+/// The name of a member is the name of its directory
+fn member_name(member: &Path) -> Option<String> {
+    match member.components().last()? {
+        Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+        _ => None,
+    }
+}
+
+fn canonicalize(dir: &Path) -> Result<PathBuf, CliError> {
+    dir.canonicalize()
+        .map_err(|err| CliError::Generic(format!("cannot find {}: {}", dir.display(), err)))
+}
+
+/// Removes the `.` components of a path, along with the `..` components which follow
+/// a directory, without looking at the file system
+pub(crate) fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => (),
+            Component::ParentDir => {
+                if matches!(normalized.components().last(), Some(Component::Normal(_))) {
+                    normalized.pop();
+                } else {
+                    normalized.push(component.as_os_str());
+                }
+            }
+            component => normalized.push(component.as_os_str()),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::{normalize_path, select_packages};
+
+    fn package(dir: &Path, dependencies: &str) {
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::write(
+            dir.join("Nargo.toml"),
+            format!("[package]\nauthors = []\n\n[dependencies]\n{}", dependencies),
+        )
+        .unwrap();
+        std::fs::write(dir.join("src/lib.nr"), "fn foo() {}").unwrap();
+    }
+
+    fn workspace() -> tempdir::TempDir {
+        let dir = tempdir::TempDir::new("workspace").unwrap();
+        std::fs::write(
+            dir.path().join("Nargo.toml"),
+            "[workspace]\nmembers = [\"circuits/a\", \"circuits/b\", \"./libs/shared\"]\n",
+        )
+        .unwrap();
+        package(&dir.path().join("circuits/a"), "shared = { path = \"../../libs/shared\" }");
+        package(&dir.path().join("circuits/b"), "shared = { path = \"../../libs/shared\" }");
+        package(&dir.path().join("libs/shared"), "");
+        dir
+    }
+
+    fn names(current_dir: &Path, package: Option<&str>) -> Vec<String> {
+        select_packages(current_dir, package).unwrap().into_iter().map(|p| p.name).collect()
+    }
+
+    #[test]
+    fn selects_workspace_members() {
+        let dir = workspace();
+        assert_eq!(names(dir.path(), None), vec!["a", "b", "shared"]);
+        assert_eq!(names(dir.path(), Some("b")), vec!["b"]);
+        assert_eq!(names(&dir.path().join("circuits/a"), None), vec!["a"]);
+        assert_eq!(names(&dir.path().join("circuits/a"), Some("shared")), vec!["shared"]);
+        assert!(select_packages(dir.path(), Some("c")).is_err());
+    }
+
+    #[test]
+    fn members_share_output_dirs() {
+        let dir = workspace();
+        let selected = select_packages(&dir.path().join("circuits/b"), None).unwrap();
+        assert_eq!(
+            selected[0].output_dir("build"),
+            dir.path().canonicalize().unwrap().join("build/b")
+        );
+
+        // Outside of a workspace, the outputs are written relative to the package
+        let outside = tempdir::TempDir::new("package").unwrap();
+        package(outside.path(), "");
+        let selected = select_packages(outside.path(), None).unwrap();
+        assert_eq!(selected[0].output_dir("build"), PathBuf::from("build"));
+        assert!(select_packages(outside.path(), Some("a")).is_err());
+    }
+
+    #[test]
+    fn path_dependencies_are_relative_to_their_package() {
+        let dir = workspace();
+        // The command may be run from anywhere
+        let driver = crate::resolver::Resolver::resolve_root_config(&dir.path().join("circuits/a"));
+        assert!(driver.is_ok());
+    }
+
+    #[test]
+    fn normalizes_paths() {
+        assert_eq!(normalize_path(Path::new("circuits/a/../../libs/./b")), PathBuf::from("libs/b"));
+        assert_eq!(normalize_path(Path::new("../shared")), PathBuf::from("../shared"));
+        assert_eq!(normalize_path(Path::new("./")), PathBuf::new());
+    }
+}
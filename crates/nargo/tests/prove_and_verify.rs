@@ -15,6 +15,7 @@ mod tests {
             Err(_) => BTreeMap::from([
                 ("exclude".to_string(), Vec::new()),
                 ("fail".to_string(), Vec::new()),
+                ("mock_exclude".to_string(), Vec::new()),
             ]),
         };
         if conf_data.get("exclude").is_none() {
@@ -23,6 +24,9 @@ mod tests {
         if conf_data.get("fail").is_none() {
             conf_data.insert("fail".to_string(), Vec::new());
         }
+        if conf_data.get("mock_exclude").is_none() {
+            conf_data.insert("mock_exclude".to_string(), Vec::new());
+        }
         conf_data
     }
 
@@ -45,7 +49,9 @@ mod tests {
                 let test_name = c.file_name().into_string();
                 match test_name {
                     Ok(str) => {
-                        if c.path().is_dir() && !conf_data["exclude"].contains(&str) {
+                        let excluded = conf_data["exclude"].contains(&str)
+                            || (cfg!(feature = "mock") && conf_data["mock_exclude"].contains(&str));
+                        if c.path().is_dir() && !excluded {
                             let r = nargo::cli::prove_and_verify("pp", &c.path(), true);
                             if conf_data["fail"].contains(&str) {
                                 assert!(!r, "{:?} should not succeed", c.file_name());
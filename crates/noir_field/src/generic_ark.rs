@@ -161,8 +161,13 @@ impl<F: PrimeField> FieldElement<F> {
             return FieldElement::from_hex(input);
         }
 
-        let fr = F::from_str(input).ok()?;
-        Some(FieldElement(fr))
+        // Parsing through a `BigUint` allows us to reject values which are not less than the modulus,
+        // rather than silently reducing them
+        let integer: BigUint = input.parse().ok()?;
+        if integer >= Self::modulus() {
+            return None;
+        }
+        Some(FieldElement::from_be_bytes_reduce(&integer.to_bytes_be()))
     }
 
     /// This is the number of bits required to represent this specific field element
@@ -359,6 +364,16 @@ impl<F: PrimeField> SubAssign for FieldElement<F> {
 
 #[cfg(test)]
 mod test {
+    #[test]
+    fn try_from_str_rejects_values_above_the_modulus() {
+        type FieldElement = crate::generic_ark::FieldElement<ark_bn254::Fr>;
+
+        let modulus = FieldElement::modulus();
+        let max = (modulus.clone() - 1u32).to_string();
+        assert_eq!(FieldElement::try_from_str(&max), Some(-FieldElement::one()));
+        assert_eq!(FieldElement::try_from_str(&modulus.to_string()), None);
+    }
+
     #[test]
     fn and() {
         let max = 10_000u32;
@@ -2,23 +2,29 @@ use std::path::PathBuf;
 
 #[derive(Debug)]
 pub enum InputParserError {
-    MissingTomlFile(PathBuf),
+    MissingInputFile(PathBuf),
     ParseTomlMap(String),
-    SaveTomlFile(std::io::Error),
+    ParseJsonMap(String),
+    SaveInputFile(std::io::Error),
     ParseStr(String),
     ParseHexStr(String),
+    FieldOverflow(String),
     DuplicateVariableName(String),
+    IntegerOutOfRange { path: String, value: String, range: String },
 }
 
 impl std::fmt::Display for InputParserError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            InputParserError::MissingTomlFile(path) => write!(f, "cannot find input file located at {:?}, run nargo build to generate the missing Prover and/or Verifier toml files", path),
+            InputParserError::MissingInputFile(path) => write!(f, "cannot find input file located at {:?}, run nargo build to generate the missing Prover and/or Verifier input files", path),
             InputParserError::ParseTomlMap(err_msg) => write!(f, "input.toml file is badly formed, could not parse, {}", err_msg),
-            InputParserError::SaveTomlFile(err) => write!(f, "could not save file to disk, {}", err),
+            InputParserError::ParseJsonMap(err_msg) => write!(f, "input.json file is badly formed, could not parse, {}", err_msg),
+            InputParserError::SaveInputFile(err) => write!(f, "could not save file to disk, {}", err),
             InputParserError::ParseStr(err_msg) => write!(f, "Expected witness values to be integers, provided value causes `{}` error", err_msg),
             InputParserError::ParseHexStr(err_msg) => write!(f, "Could not parse hex value {}", err_msg),
-            InputParserError::DuplicateVariableName(err_msg) => write!(f, "duplicate variable name {}", err_msg)
+            InputParserError::FieldOverflow(value) => write!(f, "the value {} does not fit in a field element, it must be less than the field modulus", value),
+            InputParserError::DuplicateVariableName(err_msg) => write!(f, "duplicate variable name {}", err_msg),
+            InputParserError::IntegerOutOfRange { path, value, range } => write!(f, "the value {} given for `{}` is out of range, expected an integer from {}", value, path, range)
         }
     }
 }
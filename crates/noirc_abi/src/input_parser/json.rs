@@ -0,0 +1,107 @@
+use super::{input_types_to_values, values_to_input_types, FieldFormat, InputTypes, InputValue};
+use crate::errors::InputParserError;
+use crate::Abi;
+use std::{collections::BTreeMap, path::Path};
+
+// JSON integers may be negative, as they are for TOML
+type JsonTypes = InputTypes<i64>;
+
+pub(crate) fn parse<P: AsRef<Path>>(
+    path_to_json: P,
+) -> Result<BTreeMap<String, InputValue>, InputParserError> {
+    let path_to_json = path_to_json.as_ref();
+    if !path_to_json.exists() {
+        return Err(InputParserError::MissingInputFile(path_to_json.to_path_buf()));
+    }
+    // Get input.json file as a string
+    let input_as_string = std::fs::read_to_string(path_to_json).unwrap();
+    parse_json_str(&input_as_string)
+}
+
+pub fn serialise<P: AsRef<Path>>(
+    path_to_json: P,
+    w_map: &BTreeMap<String, InputValue>,
+    abi: &Abi,
+    field_format: FieldFormat,
+) -> Result<(), InputParserError> {
+    let json_string = serialise_to_json_str(w_map, abi, field_format)?;
+    std::fs::write(path_to_json.as_ref(), json_string).map_err(InputParserError::SaveInputFile)?;
+    Ok(())
+}
+
+/// Parses input.json into a BTreeMap, converting the arguments to field elements
+fn parse_json_str(input: &str) -> Result<BTreeMap<String, InputValue>, InputParserError> {
+    let data: BTreeMap<String, JsonTypes> = serde_json::from_str(input)
+        .map_err(|err_msg| InputParserError::ParseJsonMap(err_msg.to_string()))?;
+    input_types_to_values(data)
+}
+
+fn serialise_to_json_str(
+    w_map: &BTreeMap<String, InputValue>,
+    abi: &Abi,
+    field_format: FieldFormat,
+) -> Result<String, InputParserError> {
+    let to_map: BTreeMap<String, JsonTypes> = values_to_input_types(w_map, abi, field_format);
+    serde_json::to_string_pretty(&to_map)
+        .map_err(|err_msg| InputParserError::ParseJsonMap(err_msg.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::FieldElement;
+
+    use super::{parse_json_str, serialise_to_json_str};
+    use crate::errors::InputParserError;
+    use crate::input_parser::{FieldFormat, InputValue};
+    use crate::{Abi, AbiFEType, AbiType, Sign};
+
+    #[test]
+    fn round_trips_every_kind_of_value() {
+        let json = r#"{
+            "x": "0x05",
+            "y": 7,
+            "flag": true,
+            "return": "",
+            "point": { "x": "1", "y": ["2", 3] }
+        }"#;
+        let values = parse_json_str(json).unwrap();
+
+        assert!(matches!(values["x"], InputValue::Field(f) if f == FieldElement::from(5_i128)));
+        assert!(matches!(values["y"], InputValue::Field(f) if f == FieldElement::from(7_i128)));
+        assert!(matches!(values["flag"], InputValue::Field(f) if f.is_one()));
+        assert!(matches!(values["return"], InputValue::Undefined));
+
+        let mut values = values;
+        values.remove("return");
+        let serialised =
+            serialise_to_json_str(&values, &Abi { parameters: vec![] }, FieldFormat::Hex).unwrap();
+        let reparsed = parse_json_str(&serialised).unwrap();
+        assert_eq!(reparsed.len(), values.len());
+        for (name, value) in &values {
+            assert_eq!(reparsed[name].to_fields(), value.to_fields(), "{}", name);
+        }
+        assert!(matches!(&reparsed["point"], InputValue::Struct(fields) if fields.len() == 2));
+    }
+
+    #[test]
+    fn parses_negative_signed_integers() {
+        let values = parse_json_str(r#"{ "x": -3, "y": "-4" }"#).unwrap();
+        let typ = AbiType::Integer { visibility: AbiFEType::Private, sign: Sign::Signed, width: 8 };
+        for (name, expected) in [("x", 253_i128), ("y", 252_i128)] {
+            let encoded = values[name].clone().encode_integers(&typ, name).unwrap();
+            assert!(matches!(encoded, InputValue::Field(f) if f == FieldElement::from(expected)));
+        }
+    }
+
+    #[test]
+    fn badly_formed_json_is_rejected() {
+        assert!(matches!(
+            parse_json_str(r#"{ "x": "1", }"#),
+            Err(InputParserError::ParseJsonMap(_))
+        ));
+        assert!(matches!(
+            parse_json_str(r#"{ "x": "0xzz" }"#),
+            Err(InputParserError::ParseHexStr(_))
+        ));
+    }
+}
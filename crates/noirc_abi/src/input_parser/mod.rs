@@ -1,34 +1,167 @@
+mod json;
 mod toml;
 
 use std::{collections::BTreeMap, path::Path};
 
 use acvm::FieldElement;
-use serde::Serialize;
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
 
 use crate::errors::InputParserError;
-use crate::AbiType;
+use crate::{Abi, AbiType, Sign};
 /// This is what all formats eventually transform into
 /// For example, a toml file will parse into TomlTypes
 /// and those TomlTypes will be mapped to Value
 #[derive(Debug, Clone, Serialize)]
 pub enum InputValue {
     Field(FieldElement),
-    Vec(Vec<FieldElement>),
+    /// Arrays and tuples
+    Vec(Vec<InputValue>),
+    Struct(BTreeMap<String, InputValue>),
     Undefined,
 }
 
 impl InputValue {
     /// Checks whether the ABI type matches the InputValue type
     /// and also their arity
-    pub fn matches_abi(&self, abi_param: AbiType) -> bool {
+    pub fn matches_abi(&self, abi_param: &AbiType) -> bool {
+        // Only a whole parameter may be left undefined, not one of its elements or fields
+        matches!(self, InputValue::Undefined) || self.matches_abi_type(abi_param)
+    }
+
+    fn matches_abi_type(&self, abi_param: &AbiType) -> bool {
         match (self, abi_param) {
-            (InputValue::Field(_), AbiType::Field(_)) => true,
-            (InputValue::Field(_), AbiType::Array { .. }) => false,
-            (InputValue::Field(_), AbiType::Integer { .. }) => true,
-            (InputValue::Vec(_), AbiType::Field(_)) => false,
-            (InputValue::Vec(x), AbiType::Array { length, .. }) => x.len() == length as usize,
-            (InputValue::Vec(_), AbiType::Integer { .. }) => false,
-            (InputValue::Undefined, _) => true,
+            (
+                InputValue::Field(_),
+                AbiType::Field(_) | AbiType::Integer { .. } | AbiType::Boolean(_),
+            ) => true,
+            (InputValue::Vec(elements), AbiType::Array { length, typ, .. }) => {
+                elements.len() == *length as usize
+                    && elements.iter().all(|element| element.matches_abi_type(typ))
+            }
+            (InputValue::Vec(elements), AbiType::Tuple { fields, .. }) => {
+                elements.len() == fields.len()
+                    && elements
+                        .iter()
+                        .zip(fields)
+                        .all(|(element, typ)| element.matches_abi_type(typ))
+            }
+            (InputValue::Struct(map), AbiType::Struct { fields, .. }) => {
+                map.len() == fields.len()
+                    && fields.iter().all(|(field_name, typ)| {
+                        map.get(field_name).map_or(false, |value| value.matches_abi_type(typ))
+                    })
+            }
+            _ => false,
+        }
+    }
+
+    /// Flattens the value into the field elements which are assigned to its witnesses.
+    ///
+    /// Struct fields are laid out in order of their names, as in the ABI.
+    pub fn to_fields(&self) -> Vec<FieldElement> {
+        match self {
+            InputValue::Field(element) => vec![*element],
+            InputValue::Vec(elements) => elements.iter().flat_map(InputValue::to_fields).collect(),
+            InputValue::Struct(map) => map.values().flat_map(InputValue::to_fields).collect(),
+            InputValue::Undefined => unreachable!("undefined values cannot be flattened"),
+        }
+    }
+
+    /// Applies `f` to each field element in the value along with its type,
+    /// and a path such as `x.y[2]` naming the element within the parameter.
+    ///
+    /// Values which do not match the shape of the type are left as they are,
+    /// they are reported by `matches_abi` instead.
+    fn map_fields<E>(
+        self,
+        typ: &AbiType,
+        path: &str,
+        f: &mut impl FnMut(FieldElement, &AbiType, &str) -> Result<FieldElement, E>,
+    ) -> Result<InputValue, E> {
+        let value = match (self, typ) {
+            (InputValue::Field(element), _) => InputValue::Field(f(element, typ, path)?),
+            (InputValue::Vec(elements), AbiType::Array { typ, .. }) => InputValue::Vec(
+                elements
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, element)| element.map_fields(typ, &format!("{}[{}]", path, i), f))
+                    .collect::<Result<_, _>>()?,
+            ),
+            (InputValue::Vec(elements), AbiType::Tuple { fields, .. })
+                if elements.len() == fields.len() =>
+            {
+                InputValue::Vec(
+                    elements
+                        .into_iter()
+                        .zip(fields)
+                        .enumerate()
+                        .map(|(i, (element, typ))| {
+                            element.map_fields(typ, &format!("{}.{}", path, i), f)
+                        })
+                        .collect::<Result<_, _>>()?,
+                )
+            }
+            (InputValue::Struct(values), AbiType::Struct { fields, .. }) => InputValue::Struct(
+                values
+                    .into_iter()
+                    .map(|(name, value)| match fields.get(&name) {
+                        Some(typ) => {
+                            let value = value.map_fields(typ, &format!("{}.{}", path, name), f)?;
+                            Ok((name, value))
+                        }
+                        None => Ok((name, value)),
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            (value, _) => value,
+        };
+        Ok(value)
+    }
+
+    /// Checks that each integer in the value fits in the width and sign of its type.
+    ///
+    /// Negative numbers are parsed as the negation of a field element, these are
+    /// converted here into the two's complement representation used by signed integers.
+    fn encode_integers(self, typ: &AbiType, path: &str) -> Result<InputValue, InputParserError> {
+        self.map_fields(typ, path, &mut |element, typ, path| {
+            let (sign, width) = match typ {
+                AbiType::Integer { sign, width, .. } => (*sign, *width),
+                AbiType::Boolean(_) => (Sign::Unsigned, 1),
+                _ => return Ok(element),
+            };
+            encode_integer(element, sign, width).ok_or_else(|| {
+                InputParserError::IntegerOutOfRange {
+                    path: path.to_owned(),
+                    value: element.to_string(),
+                    range: integer_range(sign, width),
+                }
+            })
+        })
+    }
+
+    /// Rebuilds a value of the given type from its flattened field elements.
+    /// This is the inverse of `to_fields`.
+    pub fn from_fields(
+        typ: &AbiType,
+        fields: &mut impl Iterator<Item = FieldElement>,
+    ) -> InputValue {
+        match typ {
+            AbiType::Field(_) | AbiType::Integer { .. } | AbiType::Boolean(_) => {
+                InputValue::Field(fields.next().expect("not enough field elements for the type"))
+            }
+            AbiType::Array { length, typ, .. } => InputValue::Vec(
+                (0..*length).map(|_| InputValue::from_fields(typ, fields)).collect(),
+            ),
+            AbiType::Tuple { fields: types, .. } => InputValue::Vec(
+                types.iter().map(|typ| InputValue::from_fields(typ, fields)).collect(),
+            ),
+            AbiType::Struct { fields: types, .. } => InputValue::Struct(
+                types
+                    .iter()
+                    .map(|(name, typ)| (name.clone(), InputValue::from_fields(typ, fields)))
+                    .collect(),
+            ),
         }
     }
 }
@@ -41,16 +174,28 @@ pub trait InitialWitnessParser {
 
 /// The different formats that are supported when parsing
 /// the initial witness values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Format {
+    Json,
     Toml,
 }
 
 impl Format {
     pub fn ext(&self) -> &'static str {
         match self {
+            Format::Json => "json",
             Format::Toml => "toml",
         }
     }
+
+    /// Returns the format which uses the given file extension
+    pub fn from_ext(ext: &str) -> Option<Format> {
+        match ext {
+            "json" => Some(Format::Json),
+            "toml" => Some(Format::Toml),
+            _ => None,
+        }
+    }
 }
 
 impl Format {
@@ -58,15 +203,27 @@ impl Format {
         &self,
         path: P,
         file_name: &str,
+        abi: &Abi,
     ) -> Result<BTreeMap<String, InputValue>, InputParserError> {
-        match self {
-            Format::Toml => {
-                let mut dir_path = path.as_ref().to_path_buf();
-                dir_path.push(file_name);
-                dir_path.set_extension(self.ext());
-                toml::parse(dir_path)
-            }
-        }
+        let mut dir_path = path.as_ref().to_path_buf();
+        dir_path.push(file_name);
+        dir_path.set_extension(self.ext());
+
+        let input_map = match self {
+            Format::Json => json::parse(dir_path),
+            Format::Toml => toml::parse(dir_path),
+        }?;
+
+        input_map
+            .into_iter()
+            .map(|(param_name, value)| match abi.parameter_type(&param_name) {
+                Some(typ) => {
+                    let value = value.encode_integers(typ, &param_name)?;
+                    Ok((param_name, value))
+                }
+                None => Ok((param_name, value)),
+            })
+            .collect()
     }
 
     pub fn serialise<P: AsRef<Path>>(
@@ -74,15 +231,437 @@ impl Format {
         path: P,
         file_name: &str,
         w_map: &BTreeMap<String, InputValue>,
+        abi: &Abi,
+        field_format: FieldFormat,
     ) -> Result<(), InputParserError> {
+        let mut dir_path = path.as_ref().to_path_buf();
+        dir_path.push(file_name);
+        dir_path.set_extension(self.ext());
+
         match self {
-            Format::Toml => {
-                let mut dir_path = path.as_ref().to_path_buf();
-                dir_path.push(file_name);
-                dir_path.set_extension(self.ext());
-                toml::serialise(dir_path, w_map)?;
+            Format::Json => json::serialise(dir_path, w_map, abi, field_format),
+            Format::Toml => toml::serialise(dir_path, w_map, abi, field_format),
+        }
+    }
+}
+
+/// How field elements are written when serialising input values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FieldFormat {
+    /// `0x` prefixed hexadecimal
+    #[default]
+    Hex,
+    /// Decimal
+    Decimal,
+}
+
+/// A value as it is read from or written to an input file.
+///
+/// Every format is read into this before being converted into `InputValue`s, the formats only
+/// differ in the integers they support, which is given by `I`.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+enum InputTypes<I> {
+    // This is most likely going to be a hex string
+    // But it is possible to support UTF-8
+    String(String),
+    // Just a regular integer, that can fit in 64 bits
+    Integer(I),
+    // Simple boolean flag
+    Bool(bool),
+    // Array of values, which is used for both arrays and tuples
+    Array(Vec<InputTypes<I>>),
+    // Struct, mapping each field name to its value
+    Table(BTreeMap<String, InputTypes<I>>),
+}
+
+/// Converts the mapping read from an input file to the native representation that the compiler
+/// understands for Inputs
+fn input_types_to_values<I: ToString>(
+    map: BTreeMap<String, InputTypes<I>>,
+) -> Result<BTreeMap<String, InputValue>, InputParserError> {
+    let mut field_map = BTreeMap::new();
+    for (parameter, value) in map {
+        let mapped_value = input_type_to_value(value)?;
+        check_map_duplicates(&mut field_map, parameter, mapped_value)?;
+    }
+
+    Ok(field_map)
+}
+
+fn input_type_to_value<I: ToString>(value: InputTypes<I>) -> Result<InputValue, InputParserError> {
+    let input_value = match value {
+        InputTypes::String(string) => match parse_str(&string)? {
+            Some(new_value) => InputValue::Field(new_value),
+            None => InputValue::Undefined,
+        },
+        InputTypes::Integer(integer) => {
+            let new_value = parse_str(&integer.to_string())?;
+            InputValue::Field(new_value.unwrap())
+        }
+        InputTypes::Bool(boolean) => {
+            InputValue::Field(if boolean { FieldElement::one() } else { FieldElement::zero() })
+        }
+        InputTypes::Array(array) => {
+            let elements =
+                array.into_iter().map(input_type_to_value).collect::<Result<Vec<_>, _>>()?;
+            InputValue::Vec(elements)
+        }
+        InputTypes::Table(table) => {
+            let fields = table
+                .into_iter()
+                .map(|(name, value)| Ok((name, input_type_to_value(value)?)))
+                .collect::<Result<BTreeMap<_, _>, InputParserError>>()?;
+            InputValue::Struct(fields)
+        }
+    };
+    Ok(input_value)
+}
+
+/// The inverse of `input_types_to_values`, field elements are written as strings so that they
+/// are not limited to the integers of the format.
+///
+/// The types of the parameters in the ABI decide how their field elements are written,
+/// values which are not in the ABI are written as fields.
+fn values_to_input_types<I>(
+    map: &BTreeMap<String, InputValue>,
+    abi: &Abi,
+    field_format: FieldFormat,
+) -> BTreeMap<String, InputTypes<I>> {
+    map.iter()
+        .map(|(parameter, value)| {
+            let typ = abi.parameter_type(parameter);
+            (parameter.clone(), value_to_input_type(value, typ, field_format))
+        })
+        .collect()
+}
+
+fn value_to_input_type<I>(
+    value: &InputValue,
+    typ: Option<&AbiType>,
+    field_format: FieldFormat,
+) -> InputTypes<I> {
+    match value {
+        InputValue::Field(f) => InputTypes::String(format_field_str(f, typ, field_format)),
+        InputValue::Vec(v) => InputTypes::Array(
+            v.iter()
+                .enumerate()
+                .map(|(i, element)| {
+                    let element_type = match typ {
+                        Some(AbiType::Array { typ, .. }) => Some(typ.as_ref()),
+                        Some(AbiType::Tuple { fields, .. }) => fields.get(i),
+                        _ => None,
+                    };
+                    value_to_input_type(element, element_type, field_format)
+                })
+                .collect(),
+        ),
+        InputValue::Struct(fields) => InputTypes::Table(
+            fields
+                .iter()
+                .map(|(name, value)| {
+                    let field_type = match typ {
+                        Some(AbiType::Struct { fields, .. }) => fields.get(name),
+                        _ => None,
+                    };
+                    (name.clone(), value_to_input_type(value, field_type, field_format))
+                })
+                .collect(),
+        ),
+        InputValue::Undefined => unreachable!(),
+    }
+}
+
+fn check_map_duplicates(
+    field_map: &mut BTreeMap<String, InputValue>,
+    parameter: String,
+    new_value: InputValue,
+) -> Result<(), InputParserError> {
+    match field_map.insert(parameter.clone(), new_value) {
+        Some(_) => Err(InputParserError::DuplicateVariableName(parameter)),
+        None => Ok(()),
+    }
+}
+
+fn two_pow(exponent: u32) -> FieldElement {
+    FieldElement::from(2_i128).pow(&FieldElement::from(exponent as i128))
+}
+
+/// Returns the representation of `value` as an integer of the given sign and width,
+/// or `None` if it is out of range
+fn encode_integer(value: FieldElement, sign: Sign, width: u32) -> Option<FieldElement> {
+    match sign {
+        Sign::Unsigned => {
+            if value.num_bits() <= width {
+                Some(value)
+            } else {
+                None
+            }
+        }
+        Sign::Signed => {
+            let min_negative = two_pow(width - 1);
+            if value < min_negative {
+                Some(value)
+            } else if -value <= min_negative {
+                // Two's complement of a negative value
+                Some(value + two_pow(width))
+            } else {
+                None
+            }
+        }
+    }
+}
+
+/// Describes the range of values allowed for an integer of the given sign and width
+fn integer_range(sign: Sign, width: u32) -> String {
+    match (sign, width) {
+        (Sign::Unsigned, width) if width < 128 => format!("0 to {}", (1_u128 << width) - 1),
+        (Sign::Unsigned, width) => format!("0 to 2^{} - 1", width),
+        (Sign::Signed, width) if width < 128 => {
+            let bound = 1_i128 << (width - 1);
+            format!("{} to {}", -bound, bound - 1)
+        }
+        (Sign::Signed, width) => format!("-2^{0} to 2^{0} - 1", width - 1),
+    }
+}
+
+/// Formats a field element of the given type for an input file.
+///
+/// Negative signed integers, which are stored in two's complement, are written as negative
+/// decimals in either format. Every other value is written as its canonical unsigned representation.
+fn format_field_str(
+    value: &FieldElement,
+    typ: Option<&AbiType>,
+    field_format: FieldFormat,
+) -> String {
+    match typ {
+        Some(AbiType::Integer { sign: Sign::Signed, width, .. })
+            if *value >= two_pow(*width - 1) =>
+        {
+            let magnitude = two_pow(*width) - *value;
+            format!("-{}", BigUint::from_bytes_be(&magnitude.to_bytes()))
+        }
+        _ => match field_format {
+            FieldFormat::Decimal => BigUint::from_bytes_be(&value.to_bytes()).to_string(),
+            FieldFormat::Hex => format!("0x{}", value.to_hex()),
+        },
+    }
+}
+
+fn parse_str(value: &str) -> Result<Option<FieldElement>, InputParserError> {
+    if value.is_empty() {
+        Ok(None)
+    } else if value.starts_with("0x") {
+        let result = FieldElement::from_hex(value);
+        if result.is_some() {
+            Ok(result)
+        } else {
+            Err(InputParserError::ParseHexStr(value.to_owned()))
+        }
+    } else if let Some(magnitude) = value.strip_prefix('-') {
+        parse_decimal_str(magnitude).map(|element| Some(-element))
+    } else {
+        parse_decimal_str(value).map(Some)
+    }
+}
+
+/// Parses a decimal string of any size, as long as it is less than the field modulus
+fn parse_decimal_str(value: &str) -> Result<FieldElement, InputParserError> {
+    if value.is_empty() || !value.bytes().all(|byte| byte.is_ascii_digit()) {
+        return Err(InputParserError::ParseStr("invalid digit found in string".to_owned()));
+    }
+    FieldElement::try_from_str(value)
+        .ok_or_else(|| InputParserError::FieldOverflow(value.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use acvm::FieldElement;
+
+    use super::{format_field_str, parse_str, FieldFormat, InputValue};
+    use crate::{errors::InputParserError, AbiFEType, AbiType, Sign};
+
+    fn integer(sign: Sign, width: u32) -> AbiType {
+        AbiType::Integer { visibility: AbiFEType::Private, sign, width }
+    }
+
+    #[test]
+    fn signed_integers_use_twos_complement() {
+        let typ = integer(Sign::Signed, 8);
+        let value = InputValue::Field(FieldElement::from(-3_i128));
+        let encoded = value.encode_integers(&typ, "x").unwrap();
+        assert!(matches!(encoded, InputValue::Field(f) if f == FieldElement::from(253_i128)));
+
+        // Negative integers are written back out as negative numbers in either format
+        let encoded = FieldElement::from(253_i128);
+        assert_eq!(format_field_str(&encoded, Some(&typ), FieldFormat::Hex), "-3");
+        assert_eq!(format_field_str(&encoded, Some(&typ), FieldFormat::Decimal), "-3");
+        let positive = FieldElement::from(127_i128);
+        assert_eq!(format_field_str(&positive, Some(&typ), FieldFormat::Decimal), "127");
+    }
+
+    #[test]
+    fn fields_above_half_the_modulus_round_trip() {
+        // Hash outputs and curve points are uniformly distributed, so most are above p/2
+        let value = -FieldElement::from(5_i128);
+        let field = AbiType::Field(AbiFEType::Private);
+        let unsigned = integer(Sign::Unsigned, 254);
+
+        for typ in [None, Some(&field), Some(&unsigned)] {
+            for field_format in [FieldFormat::Decimal, FieldFormat::Hex] {
+                let formatted = format_field_str(&value, typ, field_format);
+                assert!(!formatted.starts_with('-'), "{}", formatted);
+                assert_eq!(parse_str(&formatted).unwrap(), Some(value));
             }
         }
-        Ok(())
+        assert_eq!(
+            format_field_str(&value, Some(&field), FieldFormat::Decimal),
+            "21888242871839275222246405745257275088548364400416034343698204186575808495612"
+        );
+    }
+
+    #[test]
+    fn out_of_range_integers_are_rejected() {
+        let typ = AbiType::Array {
+            visibility: AbiFEType::Private,
+            length: 2,
+            typ: Box::new(integer(Sign::Unsigned, 8)),
+        };
+        let value = InputValue::Vec(vec![
+            InputValue::Field(FieldElement::from(255_i128)),
+            InputValue::Field(FieldElement::from(256_i128)),
+        ]);
+
+        match value.encode_integers(&typ, "x") {
+            Err(InputParserError::IntegerOutOfRange { path, range, .. }) => {
+                assert_eq!(path, "x[1]");
+                assert_eq!(range, "0 to 255");
+            }
+            other => panic!("expected an out of range error, got {:?}", other),
+        }
+
+        let too_small = InputValue::Field(FieldElement::from(-129_i128));
+        assert!(too_small.encode_integers(&integer(Sign::Signed, 8), "y").is_err());
+    }
+
+    fn field(value: i128) -> InputValue {
+        InputValue::Field(FieldElement::from(value))
+    }
+
+    fn field_elements(values: &[i128]) -> Vec<FieldElement> {
+        values.iter().map(|value| FieldElement::from(*value)).collect()
+    }
+
+    fn array(length: u128, typ: AbiType) -> AbiType {
+        AbiType::Array { visibility: AbiFEType::Private, length, typ: Box::new(typ) }
+    }
+
+    /// `{ flag: bool, matrix: [[u8; 2]; 2], pair: (Field, i8) }`
+    fn composite_type() -> AbiType {
+        let fields = BTreeMap::from([
+            ("flag".to_owned(), AbiType::Boolean(AbiFEType::Private)),
+            ("matrix".to_owned(), array(2, array(2, integer(Sign::Unsigned, 8)))),
+            (
+                "pair".to_owned(),
+                AbiType::Tuple {
+                    visibility: AbiFEType::Private,
+                    fields: vec![AbiType::Field(AbiFEType::Private), integer(Sign::Signed, 8)],
+                },
+            ),
+        ]);
+        AbiType::Struct { visibility: AbiFEType::Private, fields }
+    }
+
+    fn composite_value(flag: i128, matrix: [[i128; 2]; 2], pair: (i128, i128)) -> InputValue {
+        let matrix =
+            matrix.iter().map(|row| InputValue::Vec(row.iter().map(|x| field(*x)).collect()));
+        InputValue::Struct(BTreeMap::from([
+            ("flag".to_owned(), field(flag)),
+            ("matrix".to_owned(), InputValue::Vec(matrix.collect())),
+            ("pair".to_owned(), InputValue::Vec(vec![field(pair.0), field(pair.1)])),
+        ]))
+    }
+
+    #[test]
+    fn composite_values_round_trip_through_their_fields() {
+        let typ = composite_type();
+        let value = composite_value(1, [[1, 2], [3, 4]], (5, -3));
+        assert!(value.matches_abi(&typ));
+
+        let encoded = value.encode_integers(&typ, "s").unwrap();
+        let fields = encoded.to_fields();
+        assert_eq!(fields.len(), typ.field_count());
+        // The fields of the struct are laid out in order of their names
+        assert_eq!(fields, field_elements(&[1, 1, 2, 3, 4, 5, 253]));
+
+        let rebuilt = InputValue::from_fields(&typ, &mut fields.into_iter());
+        assert!(rebuilt.matches_abi(&typ));
+        assert_eq!(rebuilt.to_fields(), encoded.to_fields());
+    }
+
+    #[test]
+    fn composite_values_must_match_the_shape_of_their_type() {
+        let typ = composite_type();
+
+        let mut missing_field = composite_value(1, [[1, 2], [3, 4]], (5, 6));
+        if let InputValue::Struct(fields) = &mut missing_field {
+            fields.remove("flag");
+        }
+        assert!(!missing_field.matches_abi(&typ));
+
+        let short_row = InputValue::Vec(vec![InputValue::Vec(vec![field(1)]), field(2)]);
+        assert!(!short_row.matches_abi(&array(2, array(2, integer(Sign::Unsigned, 8)))));
+
+        let pair = AbiType::Tuple {
+            visibility: AbiFEType::Private,
+            fields: vec![AbiType::Field(AbiFEType::Private), AbiType::Field(AbiFEType::Private)],
+        };
+        assert!(!InputValue::Vec(vec![field(1)]).matches_abi(&pair));
+        // Only a whole parameter may be undefined
+        assert!(InputValue::Undefined.matches_abi(&pair));
+        assert!(!InputValue::Vec(vec![field(1), InputValue::Undefined]).matches_abi(&pair));
+    }
+
+    #[test]
+    fn integers_in_composite_values_are_range_checked() {
+        let typ = composite_type();
+        let out_of_range = |value: InputValue| match value.encode_integers(&typ, "s") {
+            Err(InputParserError::IntegerOutOfRange { path, range, .. }) => (path, range),
+            other => panic!("expected an out of range error, got {:?}", other),
+        };
+
+        let (path, range) = out_of_range(composite_value(2, [[1, 2], [3, 4]], (5, 6)));
+        assert_eq!((path.as_str(), range.as_str()), ("s.flag", "0 to 1"));
+
+        let (path, range) = out_of_range(composite_value(1, [[1, 2], [256, 4]], (5, 6)));
+        assert_eq!((path.as_str(), range.as_str()), ("s.matrix[1][0]", "0 to 255"));
+
+        let (path, range) = out_of_range(composite_value(1, [[1, 2], [3, 4]], (5, 128)));
+        assert_eq!((path.as_str(), range.as_str()), ("s.pair.1", "-128 to 127"));
+    }
+
+    #[test]
+    fn decimal_strings_up_to_the_modulus_are_parsed() {
+        // The modulus of the bn254 scalar field
+        let modulus =
+            "21888242871839275222246405745257275088548364400416034343698204186575808495617";
+        let max = "21888242871839275222246405745257275088548364400416034343698204186575808495616";
+
+        let max_value = parse_str(max).unwrap().unwrap();
+        assert_eq!(max_value, -FieldElement::one());
+        assert!(matches!(parse_str(modulus), Err(InputParserError::FieldOverflow(_))));
+
+        let large = "340282366920938463463374607431768211456";
+        let large_value = parse_str(large).unwrap().unwrap();
+        assert_eq!(format_field_str(&large_value, None, FieldFormat::Decimal), large);
+        assert_eq!(
+            format_field_str(&large_value, None, FieldFormat::Hex),
+            format!("0x{}", large_value.to_hex())
+        );
+
+        let negative = parse_str("-5").unwrap().unwrap();
+        assert_eq!(negative, -FieldElement::from(5_i128));
     }
 }
@@ -1,156 +1,86 @@
-use super::InputValue;
+use super::{input_types_to_values, values_to_input_types, FieldFormat, InputTypes, InputValue};
 use crate::errors::InputParserError;
-use acvm::FieldElement;
-use serde::Serialize;
-use serde_derive::Deserialize;
+use crate::Abi;
 use std::{collections::BTreeMap, path::Path};
 
+// TOML integers are signed and fit in 64 bits
+type TomlTypes = InputTypes<i64>;
+
 pub(crate) fn parse<P: AsRef<Path>>(
     path_to_toml: P,
 ) -> Result<BTreeMap<String, InputValue>, InputParserError> {
     let path_to_toml = path_to_toml.as_ref();
     if !path_to_toml.exists() {
-        return Err(InputParserError::MissingTomlFile(path_to_toml.to_path_buf()));
+        return Err(InputParserError::MissingInputFile(path_to_toml.to_path_buf()));
     }
     // Get input.toml file as a string
     let input_as_string = std::fs::read_to_string(path_to_toml).unwrap();
-
-    // Parse input.toml into a BTreeMap, converting the argument to field elements
-    let data: BTreeMap<String, TomlTypes> = toml::from_str(&input_as_string)
-        .map_err(|err_msg| InputParserError::ParseTomlMap(err_msg.to_string()))?;
-    toml_map_to_field(data)
+    parse_toml_str(&input_as_string)
 }
 
 pub fn serialise<P: AsRef<Path>>(
     path_to_toml: P,
     w_map: &BTreeMap<String, InputValue>,
+    abi: &Abi,
+    field_format: FieldFormat,
 ) -> Result<(), InputParserError> {
-    let to_map = toml_remap(w_map);
-    let toml_string = toml::to_string(&to_map)
+    let to_map: BTreeMap<String, TomlTypes> = values_to_input_types(w_map, abi, field_format);
+    // Going through a `toml::Value` ensures that structs are written after all other values,
+    // as TOML requires tables to come last.
+    let toml_string = toml::Value::try_from(&to_map)
+        .and_then(|value| toml::to_string(&value))
         .map_err(|err_msg| InputParserError::ParseTomlMap(err_msg.to_string()))?;
-    std::fs::write(path_to_toml.as_ref(), toml_string).map_err(InputParserError::SaveTomlFile)?;
+    std::fs::write(path_to_toml.as_ref(), toml_string).map_err(InputParserError::SaveInputFile)?;
     Ok(())
 }
 
-/// Converts the Toml mapping to the native representation that the compiler
-/// understands for Inputs
-fn toml_map_to_field(
-    toml_map: BTreeMap<String, TomlTypes>,
-) -> Result<BTreeMap<String, InputValue>, InputParserError> {
-    let mut field_map = BTreeMap::new();
-    for (parameter, value) in toml_map {
-        match value {
-            TomlTypes::String(string) => {
-                let new_value = parse_str(&string)?;
-                if new_value.is_none() {
-                    check_toml_map_duplicates(&mut field_map, parameter, InputValue::Undefined)?
-                } else {
-                    check_toml_map_duplicates(
-                        &mut field_map,
-                        parameter,
-                        InputValue::Field(new_value.unwrap()),
-                    )?
-                }
-            }
-            TomlTypes::Integer(integer) => {
-                let new_value = parse_str(&integer.to_string())?;
-                check_toml_map_duplicates(
-                    &mut field_map,
-                    parameter,
-                    InputValue::Field(new_value.unwrap()),
-                )?
-            }
-            TomlTypes::Bool(boolean) => {
-                let new_value = if boolean { FieldElement::one() } else { FieldElement::zero() };
-                check_toml_map_duplicates(&mut field_map, parameter, InputValue::Field(new_value))?
-            }
-            TomlTypes::ArrayNum(arr_num) => {
-                let array_elements: Vec<_> = arr_num
-                    .into_iter()
-                    .map(|elem_num| parse_str(&elem_num.to_string()).unwrap().unwrap())
-                    .collect();
-                check_toml_map_duplicates(
-                    &mut field_map,
-                    parameter,
-                    InputValue::Vec(array_elements),
-                )?
-            }
-            TomlTypes::ArrayString(arr_str) => {
-                let array_elements: Vec<_> = arr_str
-                    .into_iter()
-                    .map(|elem_str| parse_str(&elem_str).unwrap().unwrap())
-                    .collect();
-                check_toml_map_duplicates(
-                    &mut field_map,
-                    parameter,
-                    InputValue::Vec(array_elements),
-                )?
-            }
-        }
-    }
-
-    Ok(field_map)
+/// Parses input.toml into a BTreeMap, converting the arguments to field elements
+fn parse_toml_str(input: &str) -> Result<BTreeMap<String, InputValue>, InputParserError> {
+    let data: BTreeMap<String, TomlTypes> = toml::from_str(input)
+        .map_err(|err_msg| InputParserError::ParseTomlMap(err_msg.to_string()))?;
+    input_types_to_values(data)
 }
 
-fn toml_remap(map: &BTreeMap<String, InputValue>) -> BTreeMap<String, TomlTypes> {
-    let mut toml_map = BTreeMap::new();
-    for (parameter, value) in map {
-        match value {
-            InputValue::Field(f) => {
-                let f_str = format!("0x{}", f.to_hex());
-                toml_map.insert(parameter.clone(), TomlTypes::String(f_str));
-            }
-            InputValue::Vec(v) => {
-                let array = v.iter().map(|i| format!("0x{}", i.to_hex())).collect();
-                toml_map.insert(parameter.clone(), TomlTypes::ArrayString(array));
-            }
-            InputValue::Undefined => unreachable!(),
-        }
+#[cfg(test)]
+mod tests {
+    use acvm::FieldElement;
+
+    use super::parse_toml_str;
+    use crate::input_parser::InputValue;
+
+    fn fields(value: &InputValue) -> Vec<FieldElement> {
+        value.to_fields()
     }
-    toml_map
-}
 
-fn check_toml_map_duplicates(
-    field_map: &mut BTreeMap<String, InputValue>,
-    parameter: String,
-    new_value: InputValue,
-) -> Result<(), InputParserError> {
-    match field_map.insert(parameter.clone(), new_value) {
-        Some(_) => Err(InputParserError::DuplicateVariableName(parameter)),
-        None => Ok(()),
+    fn field_elements(values: &[i128]) -> Vec<FieldElement> {
+        values.iter().map(|value| FieldElement::from(*value)).collect()
     }
-}
 
-#[derive(Debug, Deserialize, Serialize)]
-#[serde(untagged)]
-enum TomlTypes {
-    // This is most likely going to be a hex string
-    // But it is possible to support UTF-8
-    String(String),
-    // Just a regular integer, that can fit in 128 bits
-    Integer(u64),
-    // Simple boolean flag
-    Bool(bool),
-    // Array of regular integers
-    ArrayNum(Vec<u64>),
-    // Array of hexadecimal integers
-    ArrayString(Vec<String>),
-}
+    #[test]
+    fn parses_structs_tuples_and_nested_arrays() {
+        let toml = r#"
+            flag = true
+            pair = ["1", 2]
+            offset = -3
+            matrix = [["1", "2"], ["3", "4"]]
+
+            [point]
+            y = "0x06"
+            x = "5"
+
+            [point.inner]
+            values = [7, 8]
+        "#;
+        let values = parse_toml_str(toml).unwrap();
+
+        assert_eq!(fields(&values["flag"]), field_elements(&[1]));
+        assert_eq!(fields(&values["pair"]), field_elements(&[1, 2]));
+        assert_eq!(fields(&values["offset"]), field_elements(&[-3]));
+        assert!(matches!(&values["matrix"], InputValue::Vec(rows) if rows.len() == 2));
+        assert_eq!(fields(&values["matrix"]), field_elements(&[1, 2, 3, 4]));
 
-fn parse_str(value: &str) -> Result<Option<FieldElement>, InputParserError> {
-    if value.is_empty() {
-        Ok(None)
-    } else if value.starts_with("0x") {
-        let result = FieldElement::from_hex(value);
-        if result.is_some() {
-            Ok(result)
-        } else {
-            Err(InputParserError::ParseHexStr(value.to_owned()))
-        }
-    } else {
-        let val: i128 = value
-            .parse::<i128>()
-            .map_err(|err_msg| InputParserError::ParseStr(err_msg.to_string()))?;
-        Ok(Some(FieldElement::from(val)))
+        // Struct fields are flattened in order of their names
+        assert!(matches!(&values["point"], InputValue::Struct(fields) if fields.len() == 3));
+        assert_eq!(fields(&values["point"]), field_elements(&[7, 8, 5, 6]));
     }
 }
@@ -1,4 +1,9 @@
-use serde::{ser::SerializeMap, Deserialize, Serialize, Serializer};
+use std::{collections::BTreeMap, convert::TryFrom};
+
+use serde::{
+    ser::{SerializeMap, SerializeSeq},
+    Deserialize, Serialize, Serializer,
+};
 
 // This is the ABI used to bridge the different TOML formats for the initial
 // witness, the partial witness generator and the interpreter.
@@ -21,8 +26,26 @@ pub mod input_parser;
 /// support.
 pub enum AbiType {
     Field(AbiFEType),
-    Array { visibility: AbiFEType, length: u128, typ: Box<AbiType> },
-    Integer { visibility: AbiFEType, sign: Sign, width: u32 },
+    Array {
+        visibility: AbiFEType,
+        length: u128,
+        typ: Box<AbiType>,
+    },
+    Integer {
+        visibility: AbiFEType,
+        sign: Sign,
+        width: u32,
+    },
+    Boolean(AbiFEType),
+    /// The fields are ordered by name, which is also the order in which they are laid out in the witness
+    Struct {
+        visibility: AbiFEType,
+        fields: BTreeMap<String, AbiType>,
+    },
+    Tuple {
+        visibility: AbiFEType,
+        fields: Vec<AbiType>,
+    },
 }
 /// This is the same as the FieldElementType in AST, without constants.
 /// We don't want the ABI to depend on Noir, so types are not shared between the two
@@ -57,25 +80,86 @@ pub enum Sign {
 impl AbiType {
     pub fn num_elements(&self) -> usize {
         match self {
-            AbiType::Field(_) | AbiType::Integer { .. } => 1,
+            AbiType::Field(_) | AbiType::Integer { .. } | AbiType::Boolean(_) => 1,
             AbiType::Array { visibility: _, length, typ: _ } => *length as usize,
+            AbiType::Struct { fields, .. } => fields.len(),
+            AbiType::Tuple { fields, .. } => fields.len(),
         }
     }
 
-    pub fn is_public(&self) -> bool {
+    /// Returns the number of field elements required to represent the type once flattened
+    pub fn field_count(&self) -> usize {
+        match self {
+            AbiType::Field(_) | AbiType::Integer { .. } | AbiType::Boolean(_) => 1,
+            AbiType::Array { length, typ, .. } => *length as usize * typ.field_count(),
+            AbiType::Struct { fields, .. } => fields.values().map(AbiType::field_count).sum(),
+            AbiType::Tuple { fields, .. } => fields.iter().map(AbiType::field_count).sum(),
+        }
+    }
+
+    pub fn visibility(&self) -> AbiFEType {
         match self {
-            AbiType::Field(fe_type) => fe_type == &AbiFEType::Public,
-            AbiType::Array { visibility, length: _, typ: _ } => visibility == &AbiFEType::Public,
-            AbiType::Integer { visibility, sign: _, width: _ } => visibility == &AbiFEType::Public,
+            AbiType::Field(visibility)
+            | AbiType::Boolean(visibility)
+            | AbiType::Array { visibility, .. }
+            | AbiType::Integer { visibility, .. }
+            | AbiType::Struct { visibility, .. }
+            | AbiType::Tuple { visibility, .. } => *visibility,
         }
     }
+
+    pub fn is_public(&self) -> bool {
+        self.visibility() == AbiFEType::Public
+    }
 }
 
-#[derive(Clone, Debug, Deserialize)]
+/// The version of the serialized ABI. This must be bumped whenever its representation changes,
+/// so that consumers do not misinterpret ABIs produced by a different compiler version.
+pub const ABI_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "VersionedAbi", try_from = "VersionedAbi")]
 pub struct Abi {
     pub parameters: Vec<(String, AbiType)>,
 }
 
+/// The serialized form of the ABI
+#[derive(Serialize, Deserialize)]
+struct VersionedAbi {
+    version: u32,
+    parameters: Vec<AbiParameter>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct AbiParameter {
+    name: String,
+    #[serde(rename = "type")]
+    typ: AbiType,
+}
+
+impl From<Abi> for VersionedAbi {
+    fn from(abi: Abi) -> VersionedAbi {
+        let parameters =
+            abi.parameters.into_iter().map(|(name, typ)| AbiParameter { name, typ }).collect();
+        VersionedAbi { version: ABI_VERSION, parameters }
+    }
+}
+
+impl TryFrom<VersionedAbi> for Abi {
+    type Error = String;
+
+    fn try_from(abi: VersionedAbi) -> Result<Abi, String> {
+        if abi.version != ABI_VERSION {
+            return Err(format!(
+                "unsupported ABI version {}, this compiler uses version {}",
+                abi.version, ABI_VERSION
+            ));
+        }
+        let parameters = abi.parameters.into_iter().map(|param| (param.name, param.typ)).collect();
+        Ok(Abi { parameters })
+    }
+}
+
 impl Abi {
     pub fn parameter_names(&self) -> Vec<&String> {
         self.parameters.iter().map(|x| &x.0).collect()
@@ -84,6 +168,10 @@ impl Abi {
     pub fn num_parameters(&self) -> usize {
         self.parameters.len()
     }
+
+    pub fn parameter_type(&self, param_name: &str) -> Option<&AbiType> {
+        self.parameters.iter().find(|(name, _)| name == param_name).map(|(_, typ)| typ)
+    }
     /// ABI with only the public parameters
     #[must_use]
     pub fn public_abi(self) -> Abi {
@@ -91,22 +179,102 @@ impl Abi {
             self.parameters.into_iter().filter(|(_, param_type)| param_type.is_public()).collect();
         Abi { parameters }
     }
+
+    /// Returns a template for the input file of the program, with an empty value for
+    /// each parameter which users can then fill in.
+    pub fn input_template(&self) -> InputTemplate<'_> {
+        InputTemplate(self)
+    }
 }
 
-impl Serialize for Abi {
+/// Serializes to a map from each parameter of an ABI to an empty value.
+///
+/// Structs are serialized as a map of their fields, while arrays and tuples are left empty.
+pub struct InputTemplate<'a>(&'a Abi);
+
+impl Serialize for InputTemplate<'_> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        let vec: Vec<u8> = Vec::new();
-        let mut map = serializer.serialize_map(Some(self.parameters.len()))?;
-        for (param_name, param_type) in &self.parameters {
-            match param_type {
-                AbiType::Field(_) => map.serialize_entry(&param_name, "")?,
-                AbiType::Array { .. } => map.serialize_entry(&param_name, &vec)?,
-                AbiType::Integer { .. } => map.serialize_entry(&param_name, "")?,
-            };
+        let mut map = serializer.serialize_map(Some(self.0.parameters.len()))?;
+        for (param_name, param_type) in &self.0.parameters {
+            map.serialize_entry(&param_name, &TypeTemplate(param_type))?;
         }
         map.end()
     }
 }
+
+struct TypeTemplate<'a>(&'a AbiType);
+
+impl Serialize for TypeTemplate<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.0 {
+            AbiType::Field(_) | AbiType::Integer { .. } | AbiType::Boolean(_) => {
+                serializer.serialize_str("")
+            }
+            AbiType::Array { .. } | AbiType::Tuple { .. } => {
+                serializer.serialize_seq(Some(0))?.end()
+            }
+            AbiType::Struct { fields, .. } => {
+                let mut map = serializer.serialize_map(Some(fields.len()))?;
+                for (field_name, field_type) in fields {
+                    map.serialize_entry(field_name, &TypeTemplate(field_type))?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{Abi, AbiFEType, AbiType, Sign};
+
+    #[test]
+    fn serialization_round_trip() {
+        let fields = BTreeMap::from([
+            ("flag".to_owned(), AbiType::Boolean(AbiFEType::Private)),
+            (
+                "values".to_owned(),
+                AbiType::Array {
+                    visibility: AbiFEType::Private,
+                    length: 3,
+                    typ: Box::new(AbiType::Integer {
+                        visibility: AbiFEType::Private,
+                        sign: Sign::Signed,
+                        width: 16,
+                    }),
+                },
+            ),
+        ]);
+        let abi = Abi {
+            parameters: vec![
+                ("x".to_owned(), AbiType::Field(AbiFEType::Public)),
+                ("s".to_owned(), AbiType::Struct { visibility: AbiFEType::Private, fields }),
+                (
+                    "t".to_owned(),
+                    AbiType::Tuple {
+                        visibility: AbiFEType::Public,
+                        fields: vec![AbiType::Field(AbiFEType::Public)],
+                    },
+                ),
+            ],
+        };
+
+        let json = serde_json::to_string(&abi).unwrap();
+        let deserialized: Abi = serde_json::from_str(&json).unwrap();
+        assert_eq!(abi, deserialized);
+    }
+
+    #[test]
+    fn rejects_unknown_version() {
+        let json = r#"{"version":0,"parameters":[]}"#;
+        assert!(serde_json::from_str::<Abi>(json).is_err());
+    }
+}
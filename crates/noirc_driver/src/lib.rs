@@ -1,39 +1,101 @@
 use acvm::acir::circuit::Circuit;
-use fm::FileType;
+use fm::{FileManager, FileType};
 use noirc_abi::Abi;
-use noirc_errors::{DiagnosableError, Reporter};
-use noirc_evaluator::create_circuit;
-use noirc_frontend::graph::{CrateId, CrateName, CrateType, LOCAL_CRATE};
+use noirc_errors::{CustomDiagnostic, DiagnosableError, Reporter};
+use noirc_evaluator::{create_circuit, unsupported_main_parameter};
+use noirc_frontend::graph::{CrateGraph, CrateId, CrateName, CrateType, LOCAL_CRATE};
 use noirc_frontend::hir::def_map::CrateDefMap;
 use noirc_frontend::hir::Context;
+use noirc_frontend::hir_def::function::FuncMeta;
 use noirc_frontend::monomorphisation::monomorphise;
+use noirc_frontend::node_interner::FuncId;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 
+pub use fm::{FileSource, InMemoryFileSource};
+pub use noirc_errors::FileDiagnostic;
+
 #[derive(Debug)]
 pub struct Driver {
     context: Context,
+    compact_witnesses: bool,
+    optimise_ranges: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CompiledProgram {
     pub circuit: Circuit,
     pub abi: Option<noirc_abi::Abi>,
+    /// The number of range constraints which the range optimiser removed from the circuit
+    pub removed_range_gates: usize,
+}
+
+/// The reasons that a program compiled from in-memory sources can fail to compile
+#[derive(Debug)]
+pub enum InMemoryCompileError {
+    /// The root file is not one of the sources, or is not a noir file
+    MissingRootFile(PathBuf),
+    Diagnostics(Vec<FileDiagnostic>),
+}
+
+impl std::fmt::Display for InMemoryCompileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InMemoryCompileError::MissingRootFile(path) => {
+                write!(f, "the entry point {} is not a noir file in the sources", path.display())
+            }
+            InMemoryCompileError::Diagnostics(diagnostics) => {
+                let messages: Vec<_> = diagnostics
+                    .iter()
+                    .map(|diagnostic| diagnostic.diagnostic.to_string())
+                    .collect();
+                write!(f, "{}", messages.join("\n"))
+            }
+        }
+    }
 }
 
 impl Driver {
     pub fn new() -> Self {
-        Driver { context: Context::default() }
+        Driver { context: Context::default(), compact_witnesses: false, optimise_ranges: true }
+    }
+
+    /// Creates a Driver which reads the files of the program from `file_source`,
+    /// rather than from the file system
+    pub fn with_file_source(file_source: Box<dyn FileSource>) -> Self {
+        let file_manager = FileManager::with_file_source(file_source);
+        Driver {
+            context: Context::new(file_manager, CrateGraph::default()),
+            compact_witnesses: false,
+            optimise_ranges: true,
+        }
     }
 
     // This is here for backwards compatibility
     // with the restricted version which only uses one file
-    pub fn compile_file(root_file: PathBuf, np_language: acvm::Language) -> CompiledProgram {
+    pub fn compile_file(
+        root_file: PathBuf,
+        np_language: acvm::Language,
+    ) -> Result<CompiledProgram, Vec<FileDiagnostic>> {
         let mut driver = Driver::new();
         driver.create_local_crate(root_file, CrateType::Binary);
         driver.into_compiled_program(np_language, false)
     }
 
+    /// Compiles the program with the root file `root_file`, reading every file from `files`
+    pub fn compile_in_memory(
+        root_file: PathBuf,
+        files: InMemoryFileSource,
+        np_language: acvm::Language,
+    ) -> Result<CompiledProgram, InMemoryCompileError> {
+        let mut driver = Driver::with_file_source(Box::new(files));
+        if driver.try_create_local_crate(&root_file, CrateType::Binary).is_none() {
+            return Err(InMemoryCompileError::MissingRootFile(root_file));
+        }
+        driver.into_compiled_program(np_language, false).map_err(InMemoryCompileError::Diagnostics)
+    }
+
     /// Compiles a file and returns true if compilation was successful
     ///
     /// This is used for tests.
@@ -43,7 +105,8 @@ impl Driver {
         for errors in &errs {
             dbg!(errors);
         }
-        errs.is_empty()
+        // Warnings do not stop a file from compiling
+        errs.iter().all(|errors| errors.errors.iter().all(|error| error.is_warning()))
     }
 
     /// Adds the File with the local crate root to the file system
@@ -57,14 +120,25 @@ impl Driver {
         root_file: P,
         crate_type: CrateType,
     ) -> CrateId {
+        let root_file = root_file.as_ref();
+        self.try_create_local_crate(root_file, crate_type)
+            .unwrap_or_else(|| panic!("cannot read the root file {}", root_file.display()))
+    }
+
+    /// Creates the Local Crate, unless its root file cannot be read or is not a noir file
+    pub fn try_create_local_crate<P: AsRef<Path>>(
+        &mut self,
+        root_file: P,
+        crate_type: CrateType,
+    ) -> Option<CrateId> {
         let dir_path = root_file.as_ref().to_path_buf();
-        let root_file_id = self.context.file_manager.add_file(&dir_path, FileType::Root).unwrap();
+        let root_file_id = self.context.file_manager.add_file(&dir_path, FileType::Root)?;
 
         let crate_id = self.context.crate_graph.add_crate_root(crate_type, root_file_id);
 
         assert!(crate_id == LOCAL_CRATE);
 
-        LOCAL_CRATE
+        Some(LOCAL_CRATE)
     }
 
     /// Creates a Non Local Crate. A Non Local Crate is any crate which is the not the crate that
@@ -119,21 +193,35 @@ impl Driver {
     }
 
     // NOTE: Maybe build could be skipped given that now it is a pass through method.
-    /// Statically analyses the local crate
-    pub fn build(&mut self) {
+    /// Statically analyses the local crate.
+    ///
+    /// Returns the warnings which were found if the crate has no errors,
+    /// otherwise every diagnostic is returned as an error, including the warnings.
+    pub fn build(&mut self) -> Result<Vec<FileDiagnostic>, Vec<FileDiagnostic>> {
         self.analyse_crate()
     }
 
-    fn analyse_crate(&mut self) {
+    fn analyse_crate(&mut self) -> Result<Vec<FileDiagnostic>, Vec<FileDiagnostic>> {
         let mut errs = vec![];
         CrateDefMap::collect_defs(LOCAL_CRATE, &mut self.context, &mut errs);
-        let mut error_count = 0;
-        for errors in &errs {
-            error_count += errors.errors.len();
-            Reporter::with_diagnostics(errors.file_id, &self.context.file_manager, &errors.errors);
+        let mut diagnostics: Vec<_> =
+            errs.into_iter().flat_map(|errors| errors.into_file_diagnostics()).collect();
+
+        // The dependencies are collected along with the local crate, but their warnings
+        // cannot be fixed by the user, so only the warnings of the local crate are kept
+        let local_crate =
+            self.context.def_map(LOCAL_CRATE).expect("ice: the local crate was just collected");
+        let local_files: HashSet<_> =
+            local_crate.modules().iter().map(|(_, module)| module.origin.file_id()).collect();
+        diagnostics.retain(|diagnostic| {
+            diagnostic.diagnostic.is_error() || local_files.contains(&diagnostic.file_id)
+        });
+
+        if diagnostics.iter().any(|diagnostic| diagnostic.diagnostic.is_error()) {
+            Err(diagnostics)
+        } else {
+            Ok(diagnostics)
         }
-
-        Reporter::finish(error_count);
     }
 
     pub fn compute_abi(&self) -> Option<Abi> {
@@ -151,47 +239,160 @@ impl Driver {
         mut self,
         np_language: acvm::Language,
         show_ssa: bool,
-    ) -> CompiledProgram {
-        self.build();
+    ) -> Result<CompiledProgram, Vec<FileDiagnostic>> {
+        self.build()?;
+        self.compile_main(np_language, show_ssa)
+    }
+
+    /// Compiles the `main` function of the local crate, which must have been built already.
+    ///
+    /// This allows the files loaded while building the crate to be inspected before compiling it.
+    pub fn compile_main(
+        &self,
+        np_language: acvm::Language,
+        show_ssa: bool,
+    ) -> Result<CompiledProgram, Vec<FileDiagnostic>> {
+        let root_file_id = self.context.crate_graph[LOCAL_CRATE].root_file_id;
 
         // Check the crate type
         // We don't panic here to allow users to `evaluate` libraries
         // which will do nothing
         if self.context.crate_graph[LOCAL_CRATE].crate_type != CrateType::Binary {
-            println!("cannot compile crate into a program as the local crate is not a binary. For libraries, please use the build command");
-            std::process::exit(1);
+            let message = "cannot compile crate into a program as the local crate is not a binary. For libraries, please use the build command";
+            return Err(vec![FileDiagnostic {
+                file_id: root_file_id,
+                diagnostic: CustomDiagnostic::from_message(message),
+            }]);
         };
 
         // Find the local crate, one should always be present
         let local_crate = self.context.def_map(LOCAL_CRATE).unwrap();
 
         // All Binaries should have a main function
-        let main_function =
-            local_crate.main_function().expect("cannot compile a program with no main function");
+        let main_function = local_crate.main_function().ok_or_else(|| {
+            vec![FileDiagnostic {
+                file_id: root_file_id,
+                diagnostic: CustomDiagnostic::from_message(
+                    "cannot compile a program with no main function",
+                ),
+            }]
+        })?;
+
+        // The FileId of the errors will be the file id of the file with the main function
+        self.compile_no_check(np_language, show_ssa, main_function)
+    }
 
+    /// Compile the given function as if it were the `main` function of the program.
+    /// This skips the checks performed by `into_compiled_program`, so it is expected
+    /// that the crate has already been built.
+    ///
+    /// The ABI of the compiled program is the ABI of `main_function`.
+    pub fn compile_no_check(
+        &self,
+        np_language: acvm::Language,
+        show_ssa: bool,
+        main_function: FuncId,
+    ) -> Result<CompiledProgram, Vec<FileDiagnostic>> {
         // Create ABI for main function
         let func_meta = self.context.def_interner.function_meta(&main_function);
+        let main_location = func_meta.location;
         let abi = func_meta.into_abi(&self.context.def_interner);
 
-        let ast = monomorphise(main_function, self.context.def_interner);
+        if let Some(message) = unsupported_main_parameter(&abi) {
+            return Err(vec![FileDiagnostic {
+                file_id: main_location.file,
+                diagnostic: CustomDiagnostic::simple_error(
+                    message,
+                    String::new(),
+                    main_location.span,
+                ),
+            }]);
+        }
+
+        let ast = monomorphise(main_function, self.context.def_interner.clone());
 
         // Compile Program
-        let circuit = match create_circuit(ast, np_language, show_ssa) {
-            Ok(circuit) => circuit,
-            Err(err) => {
-                // The FileId here will be the file id of the file with the main file
-                // Errors will be shown at the callsite without a stacktrace
-                Reporter::with_diagnostics(
-                    err.location.file,
-                    &self.context.file_manager,
-                    &[err.to_diagnostic()],
-                );
-                Reporter::finish(1);
-                unreachable!("reporter will exit before this point")
+        match create_circuit(
+            ast,
+            np_language,
+            show_ssa,
+            self.compact_witnesses,
+            self.optimise_ranges,
+        ) {
+            Ok((circuit, removed_range_gates)) => {
+                Ok(CompiledProgram { circuit, abi: Some(abi), removed_range_gates })
             }
-        };
+            Err(err) => Err(vec![FileDiagnostic {
+                file_id: err.location.file,
+                diagnostic: err.to_diagnostic(),
+            }]),
+        }
+    }
+
+    /// Removes the gates which do not affect the rest of the circuit from the compiled programs,
+    /// and renumbers their witnesses so that there are no gaps between them
+    pub fn set_compact_witnesses(&mut self, compact_witnesses: bool) {
+        self.compact_witnesses = compact_witnesses;
+    }
+
+    pub fn compacts_witnesses(&self) -> bool {
+        self.compact_witnesses
+    }
+
+    /// Removes the range constraints which are implied by other constraints from the compiled programs,
+    /// this is done unless it is disabled
+    pub fn set_optimise_ranges(&mut self, optimise_ranges: bool) {
+        self.optimise_ranges = optimise_ranges;
+    }
+
+    pub fn optimises_ranges(&self) -> bool {
+        self.optimise_ranges
+    }
+
+    /// Returns a list of all functions in the local crate marked with `#[test]`
+    /// whose names contain the given pattern string. An empty pattern string
+    /// will return all functions marked with `#[test]`.
+    pub fn get_all_test_functions_in_crate_matching(&self, pattern: &str) -> Vec<FuncId> {
+        let interner = &self.context.def_interner;
+        self.context
+            .def_map(LOCAL_CRATE)
+            .expect("The local crate should be analyzed already")
+            .get_all_test_functions(interner)
+            .filter(|id| interner.function_name(id).contains(pattern))
+            .collect()
+    }
+
+    pub fn function_name(&self, id: FuncId) -> &str {
+        self.context.def_interner.function_name(&id)
+    }
+
+    pub fn function_meta(&self, id: FuncId) -> FuncMeta {
+        self.context.def_interner.function_meta(&id)
+    }
+
+    /// Provides the source of a file which is not on the file system, see [`fm::FileManager::add_embedded_file`]
+    pub fn add_embedded_file(&mut self, path_to_file: PathBuf, source: String) {
+        self.context.file_manager.add_embedded_file(path_to_file, source);
+    }
 
-        CompiledProgram { circuit, abi: Some(abi) }
+    /// Returns the path and source of every file loaded by this Driver, in the order they were loaded
+    pub fn loaded_files(&self) -> Vec<(&Path, &str)> {
+        self.context.file_manager.loaded_files()
+    }
+
+    /// Prints the given diagnostics to stderr using the files loaded by this Driver.
+    ///
+    /// The Driver never prints diagnostics itself, this is left to the command line tools which use it.
+    pub fn report_errors(&self, diagnostics: &[FileDiagnostic]) {
+        Reporter::report_all(&self.context.file_manager, diagnostics);
+    }
+
+    /// Describes each of the given diagnostics as a line of JSON, see [`Reporter::to_json`]
+    pub fn diagnostics_to_json(&self, diagnostics: &[FileDiagnostic]) -> Vec<String> {
+        diagnostics
+            .iter()
+            .map(|diagnostic| Reporter::to_json(&self.context.file_manager, diagnostic))
+            .collect()
     }
 }
 
@@ -200,3 +401,58 @@ impl Default for Driver {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use noirc_frontend::graph::{CrateType, LOCAL_CRATE};
+
+    use super::{Driver, InMemoryCompileError, InMemoryFileSource};
+
+    fn compile(entry_point: &str, files: &[(&str, &str)]) -> Result<(), InMemoryCompileError> {
+        let files: InMemoryFileSource = files.iter().copied().collect();
+        let language = acvm::Language::PLONKCSat { width: 3 };
+        Driver::compile_in_memory(PathBuf::from(entry_point), files, language).map(|_| ())
+    }
+
+    #[test]
+    fn compiles_in_memory_sources() {
+        let files = [
+            ("src/main.nr", "mod foo;\n\nfn main(x : Field) {\n    foo::check(x);\n}\n"),
+            ("src/foo.nr", "fn check(x : Field) {\n    constrain x == 1;\n}\n"),
+        ];
+        assert!(compile("src/main.nr", &files).is_ok());
+    }
+
+    #[test]
+    fn missing_entry_points_are_errors() {
+        let files = [("src/main.nr", "fn main(x : Field) {}"), ("src/main.txt", "fn main() {}")];
+        for entry_point in &["src/lib.nr", "src/main.txt", "src"] {
+            match compile(entry_point, &files) {
+                Err(InMemoryCompileError::MissingRootFile(path)) => {
+                    assert_eq!(path, PathBuf::from(entry_point))
+                }
+                result => panic!("{} compiled to {:?}", entry_point, result),
+            }
+        }
+    }
+
+    #[test]
+    fn only_warns_about_the_local_crate() {
+        // Both crates have an unused variable
+        let main = "use dep::shared;
+            fn main(x : Field) { let y = x; constrain shared::double(x) == x + x; }";
+        let shared = "fn double(x : Field) -> Field { let unused = x; x * 2 }";
+        let files: InMemoryFileSource =
+            vec![("src/main.nr", main), ("shared/src/lib.nr", shared)].into_iter().collect();
+        let mut driver = Driver::with_file_source(Box::new(files));
+        let local_crate = driver.create_local_crate("src/main.nr", CrateType::Binary);
+        let shared = driver.create_non_local_crate("shared/src/lib.nr", CrateType::Library);
+        driver.add_dep(local_crate, shared, "shared");
+
+        let warnings = driver.build().unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].file_id, driver.context.crate_graph[LOCAL_CRATE].root_file_id);
+    }
+}
@@ -18,5 +18,9 @@ fn main() {
     driver.add_dep(LOCAL_CRATE, ncrate_id1, "coo4");
     driver.add_dep(LOCAL_CRATE, ncrate_id2, "coo3");
 
-    driver.into_compiled_program(acvm::Language::R1CS, false);
+    let result = driver.build().and_then(|_| driver.compile_main(acvm::Language::R1CS, false));
+    if let Err(diagnostics) = result {
+        driver.report_errors(&diagnostics);
+        std::process::exit(1);
+    }
 }
@@ -13,3 +13,18 @@ pub struct CollectedErrors {
     pub file_id: fm::FileId,
     pub errors: Vec<CustomDiagnostic>,
 }
+
+impl CollectedErrors {
+    /// Pairs each of the errors with the file they were found in
+    pub fn into_file_diagnostics(self) -> impl Iterator<Item = FileDiagnostic> {
+        let file_id = self.file_id;
+        self.errors.into_iter().map(move |diagnostic| FileDiagnostic { file_id, diagnostic })
+    }
+}
+
+/// A diagnostic along with the file which its spans refer to
+#[derive(Debug, PartialEq, Eq)]
+pub struct FileDiagnostic {
+    pub file_id: fm::FileId,
+    pub diagnostic: CustomDiagnostic,
+}
@@ -1,22 +1,41 @@
-use crate::Span;
+use crate::{FileDiagnostic, Span};
 use codespan_reporting::diagnostic::{Diagnostic, Label};
 use codespan_reporting::term;
-use codespan_reporting::term::termcolor::{
-    Color, ColorChoice, ColorSpec, StandardStream, WriteColor,
-};
+use codespan_reporting::term::termcolor::{ColorChoice, StandardStream};
 use fm::FileId;
-use std::io::Write;
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct CustomDiagnostic {
     message: String,
     secondaries: Vec<CustomLabel>,
     notes: Vec<String>,
+    kind: DiagnosticKind,
+}
+
+/// Errors stop the compilation of a program, while warnings are only reported
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    Error,
+    Warning,
+}
+
+impl DiagnosticKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiagnosticKind::Error => "error",
+            DiagnosticKind::Warning => "warning",
+        }
+    }
 }
 
 impl CustomDiagnostic {
     pub fn from_message(msg: &str) -> CustomDiagnostic {
-        Self { message: msg.to_owned(), secondaries: Vec::new(), notes: Vec::new() }
+        Self {
+            message: msg.to_owned(),
+            secondaries: Vec::new(),
+            notes: Vec::new(),
+            kind: DiagnosticKind::Error,
+        }
     }
 
     pub fn simple_error(
@@ -28,9 +47,33 @@ impl CustomDiagnostic {
             message: primary_message,
             secondaries: vec![CustomLabel::new(secondary_message, secondary_span)],
             notes: Vec::new(),
+            kind: DiagnosticKind::Error,
         }
     }
 
+    pub fn simple_warning(
+        primary_message: String,
+        secondary_message: String,
+        secondary_span: Span,
+    ) -> CustomDiagnostic {
+        CustomDiagnostic {
+            kind: DiagnosticKind::Warning,
+            ..CustomDiagnostic::simple_error(primary_message, secondary_message, secondary_span)
+        }
+    }
+
+    pub fn kind(&self) -> DiagnosticKind {
+        self.kind
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.kind == DiagnosticKind::Error
+    }
+
+    pub fn is_warning(&self) -> bool {
+        self.kind == DiagnosticKind::Warning
+    }
+
     pub fn add_note(&mut self, message: String) {
         self.notes.push(message);
     }
@@ -91,7 +134,11 @@ impl Reporter {
                     })
                     .collect();
 
-                Diagnostic::error()
+                let diagnostic = match cd.kind {
+                    DiagnosticKind::Error => Diagnostic::error(),
+                    DiagnosticKind::Warning => Diagnostic::warning(),
+                };
+                diagnostic
                     .with_message(&cd.message)
                     .with_labels(secondary_labels)
                     .with_notes(cd.notes.clone())
@@ -106,17 +153,108 @@ impl Reporter {
         }
     }
 
-    pub fn finish(error_count: usize) {
-        if error_count != 0 {
-            let writer = StandardStream::stderr(ColorChoice::Always);
-            let mut writer = writer.lock();
+    /// Describes the diagnostic as a single line of JSON, for tools which read the output of the compiler.
+    ///
+    /// Byte offsets are exclusive at the end, while lines and columns start at 1 and are inclusive.
+    pub fn to_json(files: &fm::FileManager, diagnostic: &FileDiagnostic) -> String {
+        let file = files
+            .as_simple_files()
+            .get(diagnostic.file_id.as_usize())
+            .expect("ice: diagnostics refer to files which were loaded");
+        let source = file.source();
 
-            writer.set_color(ColorSpec::new().set_fg(Some(Color::Red))).unwrap();
+        let labels: Vec<_> = diagnostic
+            .diagnostic
+            .secondaries
+            .iter()
+            .map(|label| {
+                // The same range as the terminal output is used, clamped to the source
+                let end = (label.span.end() as usize + 1).min(source.len());
+                let start = (label.span.start() as usize).min(end);
+                let (line_start, column_start) = line_and_column(source, start);
+                let (line_end, column_end) =
+                    line_and_column(source, end.saturating_sub(1).max(start));
+                serde_json::json!({
+                    "primary": false,
+                    "message": label.message,
+                    "byte_start": start,
+                    "byte_end": end,
+                    "line_start": line_start,
+                    "column_start": column_start,
+                    "line_end": line_end,
+                    "column_end": column_end,
+                })
+            })
+            .collect();
 
-            writeln!(&mut writer, "error: aborting due to {} previous errors", error_count)
-                .unwrap();
+        serde_json::json!({
+            "severity": diagnostic.diagnostic.kind.as_str(),
+            "message": diagnostic.diagnostic.message,
+            "file": file.name().to_string(),
+            "labels": labels,
+            "notes": diagnostic.diagnostic.notes,
+        })
+        .to_string()
+    }
 
-            std::process::exit(1);
+    /// Prints each of the diagnostics to stderr, using the files in `files`
+    pub fn report_all(files: &fm::FileManager, diagnostics: &[FileDiagnostic]) {
+        for diagnostic in diagnostics {
+            Reporter::with_diagnostics(
+                diagnostic.file_id,
+                files,
+                std::slice::from_ref(&diagnostic.diagnostic),
+            );
         }
     }
 }
+
+/// Returns the line and column of the character at `byte_index`, both starting at 1
+fn line_and_column(source: &str, byte_index: usize) -> (usize, usize) {
+    let before = &source[..byte_index];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    let column = before[line_start..].chars().count() + 1;
+    (line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use fm::{FileManager, FileType, InMemoryFileSource};
+    use std::path::Path;
+
+    use super::{line_and_column, CustomDiagnostic, Reporter};
+    use crate::{FileDiagnostic, Span};
+
+    #[test]
+    fn lines_and_columns_start_at_one() {
+        let source = "fn main() {\n    x\n}";
+        assert_eq!(line_and_column(source, 0), (1, 1));
+        assert_eq!(line_and_column(source, 12), (2, 1));
+        assert_eq!(line_and_column(source, 16), (2, 5));
+    }
+
+    #[test]
+    fn diagnostics_as_json() {
+        let files: InMemoryFileSource =
+            vec![("main.nr", "fn main() {\n    x\n}")].into_iter().collect();
+        let mut file_manager = FileManager::with_file_source(Box::new(files));
+        let file_id = file_manager.add_file(Path::new("main.nr"), FileType::Root).unwrap();
+
+        let mut diagnostic = CustomDiagnostic::simple_error(
+            "cannot find `x` in this scope".to_owned(),
+            "not found in this scope".to_owned(),
+            Span::inclusive(16, 16),
+        );
+        diagnostic.add_note("x is not defined".to_owned());
+        let json = Reporter::to_json(&file_manager, &FileDiagnostic { file_id, diagnostic });
+
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["file"], "main.nr");
+        assert_eq!(value["severity"], "error");
+        assert_eq!(value["notes"][0], "x is not defined");
+        let label = &value["labels"][0];
+        assert_eq!(label["message"], "not found in this scope");
+        assert_eq!((&label["line_start"], &label["column_start"]), (&2.into(), &5.into()));
+    }
+}
@@ -207,6 +207,22 @@ impl<'a> Interpreter<'a> {
 
                     env.store(param_name, Object::Integer(integer));
                 }
+                noirc_abi::AbiType::Boolean(visibility) => {
+                    let witness = self.add_witness_to_cs();
+                    if visibility == noirc_abi::AbiFEType::Public {
+                        self.push_public_input(witness);
+                    }
+
+                    let integer = Integer::from_witness_unconstrained(witness, 1);
+                    integer.constrain(self).map_err(|kind| kind.add_location(param_location))?;
+
+                    env.store(param_name, Object::Integer(integer));
+                }
+                noirc_abi::AbiType::Struct { .. } | noirc_abi::AbiType::Tuple { .. } => {
+                    let message = "structs and tuples are not supported by the interpreter";
+                    return Err(RuntimeErrorKind::Unimplemented(message.to_owned())
+                        .add_location(param_location));
+                }
             }
         }
 
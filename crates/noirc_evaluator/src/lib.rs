@@ -13,11 +13,12 @@ use acvm::acir::circuit::{
     Circuit, PublicInputs,
 };
 use acvm::acir::native_types::{Expression, Linear, Witness};
+use acvm::compiler::CompileOptions;
 use acvm::FieldElement;
 use acvm::Language;
 use environment::{Environment, FuncContext};
 use errors::{RuntimeError, RuntimeErrorKind};
-use noirc_abi::{AbiFEType, AbiType};
+use noirc_abi::{Abi, AbiFEType, AbiType};
 use noirc_frontend::monomorphisation::ast::*;
 
 use object::{Array, Integer, Object};
@@ -30,11 +31,18 @@ pub struct Evaluator {
     // so it is safer to use a u64, at least until clang is changed
     // to compile wasm64.
     current_witness_index: u32,
+    // The witnesses of the parameters of main are the first ones created, this is how many there are
+    num_input_witnesses: u32,
     public_inputs: Vec<Witness>,
     gates: Vec<Gate>,
 }
 
 /// Compiles the Program into ACIR and applies optimisations to the arithmetic gates
+///
+/// When `compact_witnesses` is set, the gates which do not affect the rest of the circuit are removed
+/// and the witnesses are renumbered so that there are no gaps between them. When `optimise_ranges`
+/// is set, the range constraints which are implied by other constraints are removed, and their number
+/// is returned along with the circuit.
 // XXX: We return the num_witnesses, but this is the max number of witnesses
 // Some of these could have been removed due to optimisations. We need this number because the
 // Standard format requires the number of witnesses. The max number is also fine.
@@ -43,7 +51,9 @@ pub fn create_circuit(
     program: Program,
     np_language: Language,
     enable_logging: bool,
-) -> Result<Circuit, RuntimeError> {
+    compact_witnesses: bool,
+    optimise_ranges: bool,
+) -> Result<(Circuit, usize), RuntimeError> {
     let mut evaluator = Evaluator::new();
 
     // create a new environment for the main context
@@ -54,16 +64,22 @@ pub fn create_circuit(
 
     let witness_index = evaluator.current_witness_index();
 
-    let optimised_circuit = acvm::compiler::compile(
+    let (optimised_circuit, removed_range_gates) = acvm::compiler::compile_with_options(
         Circuit {
             current_witness_index: witness_index,
             gates: evaluator.gates,
             public_inputs: PublicInputs(evaluator.public_inputs),
         },
         np_language,
+        CompileOptions { optimise_ranges },
     );
+    if compact_witnesses {
+        let compacted_circuit =
+            acvm::compiler::compact_witnesses(optimised_circuit, evaluator.num_input_witnesses);
+        return Ok((compacted_circuit, removed_range_gates));
+    }
 
-    Ok(optimised_circuit)
+    Ok((optimised_circuit, removed_range_gates))
 }
 
 impl Evaluator {
@@ -79,6 +95,7 @@ impl Evaluator {
             // following transformation to the witness index : f(i) = i + 1
             //
             current_witness_index: 0,
+            num_input_witnesses: 0,
             gates: Vec::new(),
         }
     }
@@ -114,6 +131,7 @@ impl Evaluator {
     ) -> Result<(), RuntimeError> {
         let mut igen = IRGenerator::new(program);
         self.parse_abi_alt(&mut igen);
+        self.num_input_witnesses = self.current_witness_index;
 
         // Now call the main function
         igen.codegen_main(env)?;
@@ -147,49 +165,77 @@ impl Evaluator {
         param_type: &AbiType,
         igen: &mut IRGenerator,
     ) -> Result<(), RuntimeErrorKind> {
-        match param_type {
-            AbiType::Field(visibility) => {
-                let witness = self.add_witness_to_cs();
-                if *visibility == AbiFEType::Public {
-                    self.public_inputs.push(witness);
-                }
-                igen.abi_var(name, def, node::ObjectType::NativeField, witness);
+        // The witnesses of a parameter are all created before any range constraint, so that
+        // they are contiguous and match the flattened input values assigned by the prover.
+        let witnesses: Vec<_> =
+            (0..param_type.field_count()).map(|_| self.add_witness_to_cs()).collect();
+        if param_type.visibility() == AbiFEType::Public {
+            self.public_inputs.extend(witnesses.iter().copied());
+        }
+
+        self.abi_value(name, Some(def), param_type, &mut witnesses.into_iter(), igen)?;
+        Ok(())
+    }
+
+    /// Creates the value of a parameter of main (or one of its fields) from its witnesses,
+    /// range constraining the witnesses of integers and booleans
+    fn abi_value(
+        &mut self,
+        name: &str,
+        def: Option<DefinitionId>,
+        param_type: &AbiType,
+        witnesses: &mut impl Iterator<Item = Witness>,
+        igen: &mut IRGenerator,
+    ) -> Result<ssa::code_gen::Value, RuntimeErrorKind> {
+        let value = match param_type {
+            AbiType::Field(_) | AbiType::Integer { .. } | AbiType::Boolean(_) => {
+                let witness = witnesses.next().unwrap();
+                self.constrain_abi_scalar(witness, param_type)?;
+                igen.abi_var(name, def, abi_scalar_type(param_type)?, witness)
             }
-            AbiType::Array { visibility, length, typ } => {
-                let mut witnesses = Vec::new();
-                let mut element_width = None;
-                if let AbiType::Integer { width, .. } = typ.as_ref() {
-                    element_width = Some(*width);
+            AbiType::Array { .. } => {
+                // Arrays of arrays are flattened into a single array holding the elements of each row
+                let (typ, length) = flattened_array(param_type);
+                let element_type = abi_scalar_type(typ)?;
+                let array_witnesses: Vec<_> = witnesses.by_ref().take(length as usize).collect();
+                for witness in &array_witnesses {
+                    self.constrain_abi_scalar(*witness, typ)?;
                 }
-                for _ in 0..*length {
-                    let witness = self.add_witness_to_cs();
-                    witnesses.push(witness);
-                    if let Some(ww) = element_width {
-                        ssa::acir_gen::range_constraint(witness, ww, self)?;
-                    }
-                    if *visibility == AbiFEType::Public {
-                        self.public_inputs.push(witness);
-                    }
-                }
-                igen.abi_array(name, def, typ.as_ref(), *length, witnesses);
+                igen.abi_array(name, def, element_type, length, array_witnesses)
             }
-            AbiType::Integer { visibility, sign, width } => {
-                let witness = self.add_witness_to_cs();
-                ssa::acir_gen::range_constraint(witness, *width, self)?;
-                if *visibility == AbiFEType::Public {
-                    self.public_inputs.push(witness);
+            AbiType::Struct { fields, .. } => {
+                let mut values = Vec::with_capacity(fields.len());
+                for (field_name, field_type) in fields {
+                    let field_name = format!("{}.{}", name, field_name);
+                    values.push(self.abi_value(&field_name, None, field_type, witnesses, igen)?);
                 }
-                match sign {
-                    noirc_abi::Sign::Unsigned => {
-                        igen.abi_var(name, def, node::ObjectType::Unsigned(*width), witness)
-                    }
-                    noirc_abi::Sign::Signed => {
-                        igen.abi_var(name, def, node::ObjectType::Signed(*width), witness)
-                    }
+                igen.abi_struct(def, values)
+            }
+            AbiType::Tuple { fields, .. } => {
+                let mut values = Vec::with_capacity(fields.len());
+                for (i, field_type) in fields.iter().enumerate() {
+                    let field_name = format!("{}.{}", name, i);
+                    values.push(self.abi_value(&field_name, None, field_type, witnesses, igen)?);
                 }
+                igen.abi_struct(def, values)
+            }
+        };
+        Ok(value)
+    }
+
+    /// Range constrains the witness of an integer or boolean parameter to its bit size
+    fn constrain_abi_scalar(
+        &mut self,
+        witness: Witness,
+        param_type: &AbiType,
+    ) -> Result<(), RuntimeErrorKind> {
+        match param_type {
+            AbiType::Integer { width, .. } => {
+                ssa::acir_gen::range_constraint(witness, *width, self)
             }
+            AbiType::Boolean(_) => ssa::acir_gen::range_constraint(witness, 1, self),
+            _ => Ok(()),
         }
-        Ok(())
     }
 
     /// The ABI is the intermediate representation between Noir and types like Toml
@@ -210,7 +256,105 @@ impl Evaluator {
             main_params.iter().zip(abi_params)
         {
             assert_eq!(param_name1, &param_name2);
-            self.param_to_var(param_name1, *param_id, &param_type, igen).unwrap();
+            self.param_to_var(param_name1, *param_id, &param_type, igen)
+                .expect("ice: unsupported parameters are rejected by `unsupported_main_parameter`");
+        }
+    }
+}
+
+/// Returns a description of the first parameter of main whose type cannot be compiled, if any.
+///
+/// The SSA does not support arrays of structs or tuples yet, so these cannot be used in main.
+/// Structs and tuples, including those which contain arrays, are supported.
+pub fn unsupported_main_parameter(abi: &Abi) -> Option<String> {
+    fn is_supported(typ: &AbiType) -> bool {
+        match typ {
+            AbiType::Field(_) | AbiType::Integer { .. } | AbiType::Boolean(_) => true,
+            AbiType::Array { .. } => abi_scalar_type(flattened_array(typ).0).is_ok(),
+            AbiType::Struct { fields, .. } => fields.values().all(is_supported),
+            AbiType::Tuple { fields, .. } => fields.iter().all(is_supported),
+        }
+    }
+
+    abi.parameters.iter().find(|(_, typ)| !is_supported(typ)).map(|(name, _)| {
+        format!(
+            "the parameter `{}` of main contains an array of structs or tuples, which are not yet supported as inputs",
+            name
+        )
+    })
+}
+
+/// Returns the type of the elements of an array, and their number once the rows of any arrays
+/// of arrays are flattened into a single array
+fn flattened_array(typ: &AbiType) -> (&AbiType, u128) {
+    match typ {
+        AbiType::Array { length, typ, .. } => {
+            let (element_type, row_length) = flattened_array(typ);
+            (element_type, length * row_length)
+        }
+        _ => (typ, 1),
+    }
+}
+
+/// Returns the object type of a scalar ABI type.
+///
+/// The SSA does not support arrays of structs or tuples yet, see `unsupported_main_parameter`.
+fn abi_scalar_type(param_type: &AbiType) -> Result<node::ObjectType, RuntimeErrorKind> {
+    match param_type {
+        AbiType::Field(_) => Ok(node::ObjectType::NativeField),
+        AbiType::Boolean(_) => Ok(node::ObjectType::Boolean),
+        AbiType::Integer { sign: noirc_abi::Sign::Unsigned, width, .. } => {
+            Ok(node::ObjectType::Unsigned(*width))
+        }
+        AbiType::Integer { sign: noirc_abi::Sign::Signed, width, .. } => {
+            Ok(node::ObjectType::Signed(*width))
+        }
+        AbiType::Array { .. } | AbiType::Struct { .. } | AbiType::Tuple { .. } => {
+            Err(RuntimeErrorKind::Unimplemented(
+                "arrays of structs or tuples are not yet supported as parameters of main"
+                    .to_owned(),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use noirc_abi::{Abi, AbiFEType, AbiType};
+
+    use super::unsupported_main_parameter;
+
+    fn array(typ: AbiType) -> AbiType {
+        AbiType::Array { visibility: AbiFEType::Private, length: 2, typ: Box::new(typ) }
+    }
+
+    #[test]
+    fn only_arrays_of_structs_and_tuples_are_unsupported() {
+        let field = AbiType::Field(AbiFEType::Private);
+        let point = AbiType::Struct {
+            visibility: AbiFEType::Private,
+            fields: BTreeMap::from([
+                ("x".to_owned(), field.clone()),
+                ("ys".to_owned(), array(field.clone())),
+            ]),
+        };
+        let supported = Abi {
+            parameters: vec![
+                ("xs".to_owned(), array(field.clone())),
+                ("m".to_owned(), array(array(field.clone()))),
+                ("p".to_owned(), point.clone()),
+            ],
+        };
+        assert_eq!(unsupported_main_parameter(&supported), None);
+
+        let pair =
+            AbiType::Tuple { visibility: AbiFEType::Private, fields: vec![field.clone(), field] };
+        for typ in [array(point), array(array(pair))] {
+            let abi = Abi { parameters: vec![("a".to_owned(), typ)] };
+            let message = unsupported_main_parameter(&abi).unwrap();
+            assert!(message.contains("`a`"), "{}", message);
         }
     }
 }
@@ -4,7 +4,6 @@ use super::mem::ArrayId;
 use super::node::{Binary, BinaryOp, NodeId, ObjectType, Operation, Variable};
 use super::{block, node, ssa_form};
 use std::collections::HashMap;
-use std::convert::TryInto;
 
 use super::super::environment::Environment;
 use super::super::errors::RuntimeError;
@@ -130,38 +129,31 @@ impl IRGenerator {
     pub fn abi_array(
         &mut self,
         name: &str,
-        ident_def: DefinitionId,
-        el_type: &noirc_abi::AbiType,
+        ident_def: Option<DefinitionId>,
+        element_type: ObjectType,
         len: u128,
         witness: Vec<acvm::acir::native_types::Witness>,
-    ) {
-        let element_type = match el_type {
-            noirc_abi::AbiType::Field(_) => ObjectType::NativeField,
-            noirc_abi::AbiType::Integer { sign, width, .. } => match sign {
-                noirc_abi::Sign::Unsigned => ObjectType::Unsigned(*width),
-                noirc_abi::Sign::Signed => ObjectType::Signed(*width),
-            },
-            noirc_abi::AbiType::Array { .. } => unreachable!(),
-        };
-        let (v_id, array_idx) = self.new_array(name, element_type, len as u32, Some(ident_def));
+    ) -> Value {
+        let (v_id, array_idx) = self.new_array(name, element_type, len as u32, ident_def);
         self.context.mem[array_idx].values = vecmap(witness, |w| w.into());
         self.context.get_current_block_mut().update_variable(v_id, v_id);
+        Value::Single(v_id)
     }
 
     pub fn abi_var(
         &mut self,
         name: &str,
-        ident_def: DefinitionId,
+        ident_def: Option<DefinitionId>,
         obj_type: node::ObjectType,
         witness: acvm::acir::native_types::Witness,
-    ) {
+    ) -> Value {
         //new variable - should be in a let statement? The let statement should set the type
         let var = node::Variable {
             id: NodeId::dummy(),
             name: name.to_string(),
             obj_type,
             root: None,
-            def: Some(ident_def),
+            def: ident_def,
             witness: Some(witness),
             parent_block: self.context.current_block,
         };
@@ -169,7 +161,15 @@ impl IRGenerator {
 
         self.context.get_current_block_mut().update_variable(v_id, v_id);
         let v_value = Value::Single(v_id);
-        self.variable_values.insert(ident_def, v_value); //TODO ident_def or ident_id??
+        if let Some(ident_def) = ident_def {
+            self.variable_values.insert(ident_def, v_value.clone()); //TODO ident_def or ident_id??
+        }
+        v_value
+    }
+
+    /// Groups the values of the fields of a struct or tuple parameter
+    pub fn abi_struct(&mut self, ident_def: Option<DefinitionId>, values: Vec<Value>) -> Value {
+        self.insert_new_struct(ident_def, values)
     }
 
     fn codegen_identifier(&mut self, ident: &Ident) -> Value {
@@ -207,16 +207,164 @@ impl IRGenerator {
         self.context.new_instruction(opcode, optype)
     }
 
+    /// Returns the array which is assigned to, the position of the element or row of it which
+    /// is assigned, and the dimensions of that row, see `codegen_array_position`
     fn codegen_indexed_value(
         &mut self,
         array: &LValue,
         index: &Expression,
         env: &mut Environment,
-    ) -> Result<(NodeId, NodeId), RuntimeError> {
-        let value = self.lvalue_to_value(array);
-        let lhs = value.unwrap_id();
+    ) -> Result<(NodeId, NodeId, Vec<u32>), RuntimeError> {
+        let (lhs, row, mut dims) = match array {
+            LValue::Index { array, index } => {
+                let (lhs, row, dims) = self.codegen_indexed_value(array, index, env)?;
+                (lhs, Some(row), dims)
+            }
+            _ => {
+                let lhs = self.lvalue_to_value(array).unwrap_id();
+                let dims =
+                    Self::lvalue_type(array).map_or_else(Vec::new, |typ| array_dimensions(&typ));
+                (lhs, None, dims)
+            }
+        };
         let index = self.codegen_expression(env, index)?.unwrap_id();
-        Ok((lhs, index))
+        let position = match row {
+            Some(row) => self.codegen_flat_index(row, dims.remove(0), index)?,
+            None => {
+                if !dims.is_empty() {
+                    dims.remove(0);
+                }
+                index
+            }
+        };
+        Ok((lhs, position, dims))
+    }
+
+    /// Returns the array which is indexed by `indexed_expr`, the position of the indexed element
+    /// or row in it, and the dimensions of that row, which are empty for an element.
+    ///
+    /// Arrays of arrays are flattened into a single array holding the elements of each row in turn,
+    /// so the position of a row is counted in rows, and `a[i][j]` is the element `i * len + j`.
+    fn codegen_array_position(
+        &mut self,
+        env: &mut Environment,
+        indexed_expr: &Index,
+    ) -> Result<(ArrayId, NodeId, Vec<u32>), RuntimeError> {
+        let (array_id, row, mut dims) = match indexed_expr.collection.as_ref() {
+            Expression::Index(collection) => {
+                let (array_id, row, dims) = self.codegen_array_position(env, collection)?;
+                (array_id, Some(row), dims)
+            }
+            collection => {
+                let dims = self
+                    .expression_type(collection)
+                    .map_or_else(Vec::new, |typ| array_dimensions(&typ));
+                let array = self.codegen_expression(env, collection)?.unwrap_id();
+                let array_id = match self.context.get_object_type(array) {
+                    ObjectType::Pointer(array_id) => array_id,
+                    other => unreachable!("Expected Pointer type, found {:?}", other),
+                };
+                (array_id, None, dims)
+            }
+        };
+        let index = self.codegen_expression(env, &indexed_expr.index)?.unwrap_id();
+        let position = match row {
+            Some(row) => self.codegen_flat_index(row, dims.remove(0), index)?,
+            None => {
+                if !dims.is_empty() {
+                    dims.remove(0);
+                }
+                index
+            }
+        };
+        Ok((array_id, position, dims))
+    }
+
+    /// Returns `row * row_len + index`, the position of the element `index` of a row
+    /// in the flattened array
+    fn codegen_flat_index(
+        &mut self,
+        row: NodeId,
+        row_len: u32,
+        index: NodeId,
+    ) -> Result<NodeId, RuntimeError> {
+        let row = self.cast_to_field(row)?;
+        let index = self.cast_to_field(index)?;
+        let row_len = self
+            .context
+            .get_or_create_const(FieldElement::from(row_len as u128), ObjectType::NativeField);
+        let start = self.codegen_infix_expression(row, row_len, BinaryOpKind::Multiply)?;
+        self.codegen_infix_expression(start, index, BinaryOpKind::Add)
+    }
+
+    fn cast_to_field(&mut self, id: NodeId) -> Result<NodeId, RuntimeError> {
+        if self.context.get_object_type(id) == ObjectType::NativeField {
+            Ok(id)
+        } else {
+            self.context.new_instruction(Operation::Cast(id), ObjectType::NativeField)
+        }
+    }
+
+    /// Loads the element of an array at the given index, or copies the row at the given index
+    /// of an array of arrays into a new array
+    fn codegen_index(
+        &mut self,
+        env: &mut Environment,
+        indexed_expr: &Index,
+    ) -> Result<Value, RuntimeError> {
+        let (array_id, position, dims) = self.codegen_array_position(env, indexed_expr)?;
+        let element_type = self.context.mem[array_id].element_type;
+        if dims.is_empty() {
+            let load = Operation::Load { array_id, index: position };
+            return Ok(Value::Single(self.context.new_instruction(load, element_type)?));
+        }
+
+        let row_len: u32 = dims.iter().product();
+        let (row, row_id) = self.context.new_array("", element_type, row_len, None);
+        for i in 0..row_len {
+            let row_index = self
+                .context
+                .get_or_create_const(FieldElement::from(i as u128), ObjectType::NativeField);
+            let index = self.codegen_flat_index(position, row_len, row_index)?;
+            let value =
+                self.context.new_instruction(Operation::Load { array_id, index }, element_type)?;
+            let store = Operation::Store { array_id: row_id, index: row_index, value };
+            self.context.new_instruction(store, element_type)?;
+        }
+        Ok(Value::Single(row))
+    }
+
+    /// Returns the type of the expressions which can hold an array of arrays
+    fn expression_type(&self, expr: &Expression) -> Option<Type> {
+        match expr {
+            Expression::Ident(ident) => Some(ident.typ.clone()),
+            Expression::ExtractTupleField(tuple, field) => match self.expression_type(tuple)? {
+                Type::Tuple(mut fields) => Some(fields.swap_remove(*field)),
+                _ => None,
+            },
+            Expression::Index(indexed_expr) => {
+                match self.expression_type(&indexed_expr.collection)? {
+                    Type::Array(_, element) => Some(*element),
+                    _ => None,
+                }
+            }
+            Expression::Call(call) => Some(self.program[call.func_id].return_type.clone()),
+            _ => None,
+        }
+    }
+
+    fn lvalue_type(lvalue: &LValue) -> Option<Type> {
+        match lvalue {
+            LValue::Ident(ident) => Some(ident.typ.clone()),
+            LValue::Index { array, .. } => match Self::lvalue_type(array)? {
+                Type::Array(_, element) => Some(*element),
+                _ => None,
+            },
+            LValue::MemberAccess { object, field_index } => match Self::lvalue_type(object)? {
+                Type::Tuple(mut fields) => Some(fields.swap_remove(*field_index)),
+                _ => None,
+            },
+        }
     }
 
     fn lvalue_to_value(&self, lvalue: &LValue) -> &Value {
@@ -288,11 +436,12 @@ impl IRGenerator {
                 });
                 self.insert_new_struct(def, values)
             }
-            Type::Array(len, _) => {
+            Type::Array(..) => {
                 //TODO support array of structs
                 let obj_type = node::ObjectType::from(typ);
-                let len = *len;
-                let (v_id, _) = self.new_array(base_name, obj_type, len.try_into().unwrap(), def);
+                // Arrays of arrays are flattened into a single array
+                let len = array_dimensions(typ).iter().product();
+                let (v_id, _) = self.new_array(base_name, obj_type, len, def);
                 Value::Single(v_id)
             }
             _ => {
@@ -417,9 +566,30 @@ impl IRGenerator {
                 self.variable_values.insert(ident_def, result);
             }
             LValue::Index { array, index } => {
-                let (lhs_id, array_idx) = self.codegen_indexed_value(array.as_ref(), index, env)?;
+                let (lhs_id, position, dims) =
+                    self.codegen_indexed_value(array.as_ref(), index, env)?;
                 let rhs_id = rhs.unwrap_id();
-                self.context.handle_assign(lhs_id, Some(array_idx), rhs_id)?;
+                if dims.is_empty() {
+                    self.context.handle_assign(lhs_id, Some(position), rhs_id)?;
+                } else {
+                    // A row of an array of arrays is assigned element by element
+                    let rhs_array = match self.context.get_object_type(rhs_id) {
+                        ObjectType::Pointer(array_id) => array_id,
+                        other => unreachable!("Expected Pointer type, found {:?}", other),
+                    };
+                    let element_type = self.context.mem[rhs_array].element_type;
+                    let row_len: u32 = dims.iter().product();
+                    for i in 0..row_len {
+                        let row_index = self.context.get_or_create_const(
+                            FieldElement::from(i as u128),
+                            ObjectType::NativeField,
+                        );
+                        let load = Operation::Load { array_id: rhs_array, index: row_index };
+                        let value = self.context.new_instruction(load, element_type)?;
+                        let index = self.codegen_flat_index(position, row_len, row_index)?;
+                        self.context.handle_assign(lhs_id, Some(index), value)?;
+                    }
+                }
             }
             LValue::MemberAccess { object: _, field_index } => {
                 // TODO: This is incorrect for nested structs
@@ -506,20 +676,7 @@ impl IRGenerator {
 
                 Ok(Value::Single(self.context.new_instruction(Operation::Cast(lhs), rtype)?))
             }
-            Expression::Index(indexed_expr) => {
-                // Evaluate the 'array' expression
-                let expr_node = self.codegen_expression(env, &indexed_expr.collection)?.unwrap_id();
-                let array = match self.context.get_object_type(expr_node) {
-                    ObjectType::Pointer(array_id) => &self.context.mem[array_id],
-                    other => unreachable!("Expected Pointer type, found {:?}", other),
-                };
-                let array_id = array.id;
-                let e_type = array.element_type;
-                // Evaluate the index expression
-                let index_as_obj = self.codegen_expression(env, &indexed_expr.index)?.unwrap_id();
-                let load = Operation::Load { array_id, index: index_as_obj };
-                Ok(Value::Single(self.context.new_instruction(load, e_type)?))
-            }
+            Expression::Index(indexed_expr) => self.codegen_index(env, indexed_expr),
             Expression::Call(call_expr) => {
                 if self.context.get_ssafunc(call_expr.func_id).is_none() {
                     let index = self.context.get_function_index();
@@ -799,3 +956,17 @@ impl IRGenerator {
         Ok(v1.zip(&v2, &mut phi))
     }
 }
+
+/// Returns the lengths of the dimensions of an array type, from the outermost to the innermost.
+///
+/// Arrays of arrays are flattened into a single array of the elements of their rows.
+fn array_dimensions(typ: &Type) -> Vec<u32> {
+    match typ {
+        Type::Array(len, element) => {
+            let mut dims = vec![*len as u32];
+            dims.extend(array_dimensions(element));
+            dims
+        }
+        _ => Vec::new(),
+    }
+}
@@ -1,6 +1,6 @@
 use std::fmt::Display;
 
-use crate::token::{Attribute, Token};
+use crate::token::{Attribute, Lint, Token};
 use crate::util::vecmap;
 use crate::{Ident, Path, Pattern, Recoverable, Statement, UnresolvedType};
 use acvm::FieldElement;
@@ -350,6 +350,8 @@ pub struct IfExpression {
 pub struct FunctionDefinition {
     pub name: Ident,
     pub attribute: Option<Attribute>, // XXX: Currently we only have one attribute defined. If more attributes are needed per function, we can make this a vector and make attribute definition more expressive
+    /// The lints whose warnings are silenced in this function, from its `#[allow(...)]` attributes
+    pub allowed_lints: Vec<Lint>,
     pub generics: Vec<Ident>,
     pub parameters: Vec<(Pattern, UnresolvedType, noirc_abi::AbiFEType)>,
     pub body: BlockExpression,
@@ -585,6 +587,9 @@ impl Display for IfExpression {
 
 impl Display for FunctionDefinition {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for lint in &self.allowed_lints {
+            writeln!(f, "{}", Attribute::Allow(*lint))?;
+        }
         if let Some(attribute) = &self.attribute {
             writeln!(f, "{}", attribute)?;
         }
@@ -1,7 +1,7 @@
 use crate::graph::CrateId;
 use crate::hir::def_collector::dc_crate::DefCollector;
 use crate::hir::Context;
-use crate::node_interner::FuncId;
+use crate::node_interner::{FuncId, NodeInterner};
 use crate::parser::{parse_program, ParsedModule};
 use arena::{Arena, Index};
 use fm::{FileId, FileManager};
@@ -104,6 +104,22 @@ impl CrateDefMap {
         root_module.scope.find_func_with_name(&MAIN_FUNCTION.into())
     }
 
+    /// Go through all modules in this crate and find all functions
+    /// marked with the `#[test]` attribute
+    pub fn get_all_test_functions<'a>(
+        &'a self,
+        interner: &'a NodeInterner,
+    ) -> impl Iterator<Item = FuncId> + 'a {
+        self.modules.iter().flat_map(move |(_, module)| {
+            module.scope.definitions().into_iter().filter_map(|def| def.as_function()).filter(
+                move |func_id| {
+                    let attributes = interner.function_meta(func_id).attributes;
+                    matches!(attributes, Some(attribute) if attribute.is_test())
+                },
+            )
+        })
+    }
+
     pub fn root_file_id(&self) -> FileId {
         let root_module = &self.modules()[self.root.0];
         root_module.origin.into()
@@ -2,6 +2,7 @@ use noirc_errors::CustomDiagnostic as Diagnostic;
 pub use noirc_errors::Span;
 use thiserror::Error;
 
+use crate::token::Lint;
 use crate::Ident;
 
 #[derive(Error, Debug, Clone, PartialEq, Eq)]
@@ -41,6 +42,16 @@ pub enum ResolverError {
 }
 
 impl ResolverError {
+    /// The lint which this error is a warning of, if it is not a hard error
+    pub fn lint(&self) -> Option<Lint> {
+        match self {
+            ResolverError::UnusedVariable { .. } => Some(Lint::UnusedVariables),
+            ResolverError::UnnecessaryMut { .. } => Some(Lint::UnnecessaryMut),
+            ResolverError::UnnecessaryPub { .. } => Some(Lint::UnnecessaryPub),
+            _ => None,
+        }
+    }
+
     /// Only user errors can be transformed into a Diagnostic
     /// ICEs will make the compiler panic, as they could affect the
     /// soundness of the generated program
@@ -58,12 +69,12 @@ impl ResolverError {
             ResolverError::UnusedVariable { ident } => {
                 let name = &ident.0.contents;
 
-                let mut diag = Diagnostic::simple_error(
+                let mut diag = Diagnostic::simple_warning(
                     format!("unused variable {}", name),
                     "unused variable ".to_string(),
                     ident.0.span(),
                 );
-                let message = format!("A new variable usually means a constraint has been added and is being unused. \n For this reason, it is almost always a bug to declare a variable and not use it. \n help: if this is intentional, prefix it with an underscore: `_{}`", name);
+                let message = format!("A new variable usually means a constraint has been added and is being unused. \n For this reason, it is almost always a bug to declare a variable and not use it. \n help: if this is intentional, prefix it with an underscore: `_{}`, or add `#[allow(unused_variables)]` to the function", name);
                 diag.add_note(message);
                 diag
             }
@@ -133,7 +144,7 @@ impl ResolverError {
                 error
             }
             ResolverError::UnnecessaryMut { first_mut, second_mut } => {
-                let mut error = Diagnostic::simple_error(
+                let mut error = Diagnostic::simple_warning(
                     "'mut' here is not necessary".to_owned(),
                     "".to_owned(),
                     second_mut,
@@ -147,7 +158,7 @@ impl ResolverError {
             ResolverError::UnnecessaryPub { ident } => {
                 let name = &ident.0.contents;
 
-                let mut diag = Diagnostic::simple_error(
+                let mut diag = Diagnostic::simple_warning(
                     format!("unnecessary pub keyword on parameter for function {}", name),
                     "unnecessary pub parameter".to_string(),
                     ident.0.span(),
@@ -30,6 +30,7 @@ use crate::graph::CrateId;
 use crate::hir::def_map::{ModuleDefId, TryFromModuleDefId};
 use crate::hir_def::stmt::{HirAssignStatement, HirLValue, HirPattern};
 use crate::node_interner::{DefinitionId, ExprId, FuncId, NodeInterner, StmtId, StructId};
+use crate::token::Lint;
 use crate::util::vecmap;
 use crate::{
     hir::{def_map::CrateDefMap, resolution::path_resolver::PathResolver},
@@ -71,6 +72,9 @@ pub struct Resolver<'a> {
     /// Contains a mapping of the current struct's generics to
     /// unique type variables if we're resolving a struct. Empty otherwise.
     generics: HashMap<Rc<String>, (TypeVariable, Span)>,
+
+    /// The lints which are allowed in the function being resolved, their warnings are not reported
+    allowed_lints: Vec<Lint>,
 }
 
 impl<'a> Resolver<'a> {
@@ -89,6 +93,7 @@ impl<'a> Resolver<'a> {
             generics: HashMap::new(),
             errors: Vec::new(),
             file,
+            allowed_lints: Vec::new(),
         }
     }
 
@@ -97,6 +102,11 @@ impl<'a> Resolver<'a> {
     }
 
     fn push_err(&mut self, err: ResolverError) {
+        if let Some(lint) = err.lint() {
+            if self.allowed_lints.contains(&lint) {
+                return;
+            }
+        }
         self.errors.push(err)
     }
 
@@ -109,6 +119,7 @@ impl<'a> Resolver<'a> {
         mut self,
         func: NoirFunction,
     ) -> (HirFunction, FuncMeta, Vec<ResolverError>) {
+        self.allowed_lints = func.def.allowed_lints.clone();
         self.scopes.start_function();
 
         // Check whether the function has globals in the local module and add them to the scope
@@ -1041,6 +1052,31 @@ mod test {
         }
     }
 
+    #[test]
+    fn allowed_lints_are_not_reported() {
+        let src = r#"
+            #[allow(unused_variables)]
+            fn main(x : Field) {
+                let y = x + x;
+                constrain x == x;
+            }
+
+            #[allow(unused_variables)]
+            fn foo(x : pub Field) {
+                let z = w;
+            }
+        "#;
+
+        let errors = resolve_src_code(src, vec!["main", "foo"]);
+
+        // Only the lints which were allowed are silenced
+        assert!(errors.len() == 2, "Expected 2 errors, got: {:?}", errors);
+        assert!(matches!(&errors[0], ResolverError::UnnecessaryPub { .. }));
+        assert!(
+            matches!(&errors[1], ResolverError::VariableNotDeclared { name, .. } if name == "w")
+        );
+    }
+
     #[test]
     fn resolve_unresolved_var() {
         let src = r#"
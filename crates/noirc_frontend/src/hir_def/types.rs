@@ -965,14 +965,22 @@ impl Type {
                 TypeBinding::Bound(typ) => typ.as_abi_type(fe_type),
                 TypeBinding::Unbound(_) => Type::default_int_type(None).as_abi_type(fe_type),
             },
-            Type::Bool(_) => {
-                AbiType::Integer { sign: noirc_abi::Sign::Unsigned, width: 1, visibility: fe_type }
-            }
+            Type::Bool(_) => AbiType::Boolean(fe_type),
             Type::Error => unreachable!(),
             Type::Unit => unreachable!(),
             Type::ArrayLength(_) => unreachable!(),
-            Type::Struct(..) => todo!("as_abi_type not yet implemented for struct types"),
-            Type::Tuple(_) => todo!("as_abi_type not yet implemented for tuple types"),
+            Type::Struct(def, args) => {
+                let fields = def.borrow().get_fields(args);
+                let fields = fields
+                    .into_iter()
+                    .map(|(name, typ)| (name, typ.as_abi_type(fe_type)))
+                    .collect();
+                AbiType::Struct { visibility: fe_type, fields }
+            }
+            Type::Tuple(fields) => {
+                let fields = vecmap(fields, |typ| typ.as_abi_type(fe_type));
+                AbiType::Tuple { visibility: fe_type, fields }
+            }
             Type::TypeVariable(_) => unreachable!(),
             Type::NamedGeneric(..) => unreachable!(),
             Type::Forall(..) => unreachable!(),
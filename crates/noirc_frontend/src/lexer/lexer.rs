@@ -435,6 +435,46 @@ fn test_custom_gate_syntax() {
     }
 }
 
+#[test]
+fn test_attribute_test() {
+    use super::token::TestScope;
+
+    let input = "#[test]#[test(should_fail)]";
+
+    let expected = vec![
+        Token::Attribute(Attribute::Test(TestScope::None)),
+        Token::Attribute(Attribute::Test(TestScope::ShouldFail)),
+    ];
+
+    let mut lexer = Lexer::new(input);
+    for token in expected.into_iter() {
+        let got = lexer.next_token().unwrap();
+        assert_eq!(got, token);
+    }
+}
+
+#[test]
+fn test_attribute_allow() {
+    use super::token::Lint;
+
+    let input = "#[allow(unused_variables)]#[allow(unnecessary_pub)]";
+
+    let expected = vec![
+        Token::Attribute(Attribute::Allow(Lint::UnusedVariables)),
+        Token::Attribute(Attribute::Allow(Lint::UnnecessaryPub)),
+    ];
+
+    let mut lexer = Lexer::new(input);
+    for token in expected.into_iter() {
+        let got = lexer.next_token().unwrap();
+        assert_eq!(got, token);
+    }
+
+    // Only known lints can be allowed
+    let mut lexer = Lexer::new("#[allow(everything)]");
+    assert!(lexer.next_token().is_err());
+}
+
 #[test]
 fn test_int_type() {
     let input = "u16 i16 i108 u104.5";
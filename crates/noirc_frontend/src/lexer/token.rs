@@ -286,6 +286,69 @@ impl IntType {
 pub enum Attribute {
     Foreign(String),
     Builtin(String),
+    Test(TestScope),
+    /// Silences the warnings of the given lint inside of the function, e.g. `#[allow(unused_variables)]`
+    Allow(Lint),
+}
+
+/// A category of warnings, which can be allowed on a function
+#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone, PartialOrd, Ord)]
+pub enum Lint {
+    UnusedVariables,
+    UnnecessaryMut,
+    UnnecessaryPub,
+}
+
+impl Lint {
+    fn lookup_str(string: &str) -> Option<Lint> {
+        match string {
+            "unused_variables" => Some(Lint::UnusedVariables),
+            "unnecessary_mut" => Some(Lint::UnnecessaryMut),
+            "unnecessary_pub" => Some(Lint::UnnecessaryPub),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Lint::UnusedVariables => "unused_variables",
+            Lint::UnnecessaryMut => "unnecessary_mut",
+            Lint::UnnecessaryPub => "unnecessary_pub",
+        }
+    }
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Expected outcome of a function marked with the `#[test]` attribute
+#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone, PartialOrd, Ord)]
+pub enum TestScope {
+    /// The test passes if all of its constraints are satisfied
+    None,
+    /// The test passes if at least one of its constraints cannot be satisfied
+    ShouldFail,
+}
+
+impl TestScope {
+    fn lookup_str(string: &str) -> Option<TestScope> {
+        match string {
+            "should_fail" => Some(TestScope::ShouldFail),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TestScope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TestScope::None => write!(f, ""),
+            TestScope::ShouldFail => write!(f, "(should_fail)"),
+        }
+    }
 }
 
 impl fmt::Display for Attribute {
@@ -293,6 +356,8 @@ impl fmt::Display for Attribute {
         match *self {
             Attribute::Foreign(ref k) => write!(f, "#[foreign({})]", k),
             Attribute::Builtin(ref k) => write!(f, "#[builtin({})]", k),
+            Attribute::Test(ref scope) => write!(f, "#[test{}]", scope),
+            Attribute::Allow(ref lint) => write!(f, "#[allow({})]", lint),
         }
     }
 }
@@ -306,34 +371,30 @@ impl Attribute {
             .filter(|string_segment| !string_segment.is_empty())
             .collect();
 
-        if word_segments.len() != 2 {
-            return Err(LexerErrorKind::MalformedFuncAttribute { span, found: word.to_owned() });
-        }
-
-        let attribute_type = word_segments[0];
-        let attribute_name = word_segments[1];
+        let malformed = || LexerErrorKind::MalformedFuncAttribute { span, found: word.to_owned() };
 
-        let tok = match attribute_type {
-            "foreign" => Token::Attribute(Attribute::Foreign(attribute_name.to_string())),
-            "builtin" => Token::Attribute(Attribute::Builtin(attribute_name.to_string())),
-            _ => {
-                return Err(LexerErrorKind::MalformedFuncAttribute { span, found: word.to_owned() })
-            }
+        let attribute = match word_segments.as_slice() {
+            ["foreign", name] => Attribute::Foreign(name.to_string()),
+            ["builtin", name] => Attribute::Builtin(name.to_string()),
+            ["test"] => Attribute::Test(TestScope::None),
+            ["test", scope] => Attribute::Test(TestScope::lookup_str(scope).ok_or_else(malformed)?),
+            ["allow", lint] => Attribute::Allow(Lint::lookup_str(lint).ok_or_else(malformed)?),
+            _ => return Err(malformed()),
         };
-        Ok(tok)
+        Ok(Token::Attribute(attribute))
     }
 
     pub fn builtin(self) -> Option<String> {
         match self {
-            Attribute::Foreign(_) => None,
             Attribute::Builtin(name) => Some(name),
+            Attribute::Foreign(_) | Attribute::Test(_) | Attribute::Allow(_) => None,
         }
     }
 
     pub fn foreign(self) -> Option<String> {
         match self {
             Attribute::Foreign(name) => Some(name),
-            Attribute::Builtin(_) => None,
+            Attribute::Builtin(_) | Attribute::Test(_) | Attribute::Allow(_) => None,
         }
     }
 
@@ -343,6 +404,16 @@ impl Attribute {
     pub fn is_low_level(&self) -> bool {
         matches!(self, Attribute::Foreign(_) | Attribute::Builtin(_))
     }
+    pub fn is_test(&self) -> bool {
+        matches!(self, Attribute::Test(_))
+    }
+
+    pub fn allowed_lint(&self) -> Option<Lint> {
+        match self {
+            Attribute::Allow(lint) => Some(*lint),
+            _ => None,
+        }
+    }
 }
 
 impl AsRef<str> for Attribute {
@@ -350,6 +421,8 @@ impl AsRef<str> for Attribute {
         match self {
             Attribute::Foreign(string) => string,
             Attribute::Builtin(string) => string,
+            Attribute::Test(_) => "test",
+            Attribute::Allow(lint) => lint.as_str(),
         }
     }
 }
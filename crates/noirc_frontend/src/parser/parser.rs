@@ -8,7 +8,7 @@ use crate::ast::{
 };
 use crate::lexer::Lexer;
 use crate::parser::{force, ignore_then_commit, statement_recovery};
-use crate::token::{Attribute, Keyword, Token, TokenKind};
+use crate::token::{Attribute, Keyword, Lint, Token, TokenKind};
 use crate::util::vecmap;
 use crate::{
     AssignStatement, BinaryOp, BinaryOpKind, BlockExpression, Comptime, ConstrainStatement,
@@ -92,8 +92,7 @@ fn submodule(module_parser: impl NoirParser<ParsedModule>) -> impl NoirParser<To
 }
 
 fn function_definition(allow_self: bool) -> impl NoirParser<NoirFunction> {
-    attribute()
-        .or_not()
+    attributes()
         .then_ignore(keyword(Keyword::Fn))
         .then(ident())
         .then(generics())
@@ -102,13 +101,17 @@ fn function_definition(allow_self: bool) -> impl NoirParser<NoirFunction> {
         .then(block(expression()))
         .map(
             |(
-                ((((attribute, name), generics), parameters), (return_visibility, return_type)),
+                (
+                    ((((attribute, allowed_lints), name), generics), parameters),
+                    (return_visibility, return_type),
+                ),
                 body,
             )| {
                 FunctionDefinition {
                     span: name.0.span(),
                     name,
                     attribute, // XXX: Currently we only have one attribute defined. If more attributes are needed per function, we can make this a vector and make attribute definition more expressive
+                    allowed_lints,
                     generics,
                     parameters,
                     body,
@@ -165,6 +168,27 @@ fn attribute() -> impl NoirParser<Attribute> {
     })
 }
 
+/// Parses the attributes of a function, separating the lints it allows from its other attribute
+fn attributes() -> impl NoirParser<(Option<Attribute>, Vec<Lint>)> {
+    attribute().map_with_span(|attribute, span| (attribute, span)).repeated().validate(
+        |attributes, _span, emit| {
+            let mut function_attribute = None;
+            let mut allowed_lints = Vec::new();
+            for (attribute, span) in attributes {
+                match attribute.allowed_lint() {
+                    Some(lint) => allowed_lints.push(lint),
+                    None if function_attribute.is_none() => function_attribute = Some(attribute),
+                    None => emit(ParserError::with_reason(
+                        "functions can only have one attribute besides #[allow(...)]".to_owned(),
+                        span,
+                    )),
+                }
+            }
+            (function_attribute, allowed_lints)
+        },
+    )
+}
+
 fn struct_fields() -> impl NoirParser<Vec<(Ident, UnresolvedType)>> {
     ident()
         .then_ignore(just(Token::Colon))
@@ -1102,6 +1126,29 @@ mod test {
         );
     }
 
+    #[test]
+    fn parse_function_attributes() {
+        let functions = parse_all(
+            function_definition(false),
+            vec![
+                "#[allow(unused_variables)] fn f(x: Field) {}",
+                "#[allow(unused_variables)] #[test] #[allow(unnecessary_mut)] fn f() {}",
+            ],
+        );
+        assert_eq!(functions[0].def.allowed_lints, vec![Lint::UnusedVariables]);
+        assert_eq!(functions[0].def.attribute, None);
+        assert_eq!(
+            functions[1].def.allowed_lints,
+            vec![Lint::UnusedVariables, Lint::UnnecessaryMut]
+        );
+        assert!(functions[1].attribute().unwrap().is_test());
+
+        parse_all_failing(
+            function_definition(false),
+            vec!["#[test] #[builtin(sum)] fn f() {}", "#[allow(unused_variables)] let x = 1;"],
+        );
+    }
+
     #[test]
     fn parse_parenthesized_expression() {
         parse_all(atom(expression()), vec!["(0)", "(x+a)", "({(({{({(nested)})}}))})"]);
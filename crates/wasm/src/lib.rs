@@ -1,28 +1,62 @@
 use acvm::acir::circuit::Circuit;
+use noirc_driver::{CompiledProgram, FileDiagnostic, InMemoryFileSource};
+use std::collections::HashMap;
 use std::path::PathBuf;
 use wasm_bindgen::prelude::*;
 
+// Converts the result of a compilation into a compiled program,
+// or into an error containing the messages of the diagnostics
+fn into_js_result(
+    result: Result<CompiledProgram, Vec<FileDiagnostic>>,
+) -> Result<JsValue, JsValue> {
+    match result {
+        Ok(compiled_program) => Ok(JsValue::from_serde(&compiled_program).unwrap()),
+        Err(diagnostics) => {
+            let messages: Vec<_> =
+                diagnostics.iter().map(|diagnostic| diagnostic.diagnostic.to_string()).collect();
+            Err(JsValue::from_str(&messages.join("\n")))
+        }
+    }
+}
+
 // Returns a compiled program which is the ACIR circuit along with the ABI
 #[wasm_bindgen]
-pub fn compile(src: String) -> JsValue {
+pub fn compile(src: String) -> Result<JsValue, JsValue> {
     console_error_panic_hook::set_once();
     // For now we default to plonk width = 3, though we can add it as a parameter
     let language = acvm::Language::PLONKCSat { width: 3 };
     let path = PathBuf::from(src);
-    let compiled_program = noirc_driver::Driver::compile_file(path, language);
-    JsValue::from_serde(&compiled_program).unwrap()
+    into_js_result(noirc_driver::Driver::compile_file(path, language))
+}
+
+// Compiles a program from its sources rather than reading them from files.
+// `sources` is an object from the path of each file to its source, it must contain `entry_point`
+#[wasm_bindgen]
+pub fn compile_sources(entry_point: String, sources: JsValue) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+    let language = acvm::Language::PLONKCSat { width: 3 };
+    let sources: HashMap<String, String> = sources.into_serde().map_err(|err| {
+        JsValue::from_str(&format!("the sources are not an object of strings: {}", err))
+    })?;
+    let files: InMemoryFileSource = sources.into_iter().collect();
+    match noirc_driver::Driver::compile_in_memory(PathBuf::from(entry_point), files, language) {
+        Ok(compiled_program) => into_js_result(Ok(compiled_program)),
+        Err(err) => Err(JsValue::from_str(&err.to_string())),
+    }
 }
 // Deserialises bytes into ACIR structure
 #[wasm_bindgen]
-pub fn acir_from_bytes(bytes: Vec<u8>) -> JsValue {
+pub fn acir_from_bytes(bytes: Vec<u8>) -> Result<JsValue, JsValue> {
     console_error_panic_hook::set_once();
-    let circuit = Circuit::from_bytes(&bytes);
-    JsValue::from_serde(&circuit).unwrap()
+    let circuit = Circuit::from_bytes(&bytes).map_err(|err| JsValue::from_str(&err.to_string()))?;
+    Ok(JsValue::from_serde(&circuit).unwrap())
 }
 
 #[wasm_bindgen]
-pub fn acir_to_bytes(acir: JsValue) -> Vec<u8> {
+pub fn acir_to_bytes(acir: JsValue) -> Result<Vec<u8>, JsValue> {
     console_error_panic_hook::set_once();
-    let circuit: Circuit = acir.into_serde().unwrap();
-    circuit.to_bytes()
+    let circuit: Circuit = acir
+        .into_serde()
+        .map_err(|err| JsValue::from_str(&format!("the value is not an ACIR circuit: {}", err)))?;
+    Ok(circuit.to_bytes())
 }